@@ -0,0 +1,626 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::robot::{
+    ActuatorParams, JointConvention, JointLimits, LinkParams, Payload, RobotArm, Transform, UrdfError, UrdfModel, Vec3,
+};
+use crate::sim::{ControllerParams, Integrator, PhysicsParams, SpeedProfile};
+
+/// Arm, home pose, pick&place task, scene, physics and controller settings, loaded from a TOML or JSON file.
+/// Every field is optional in the file; missing ones keep the shipped defaults (`SimConfig::default()`),
+/// also inside a table that sets only some of its keys.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimConfig {
+    pub arm: ArmConfig,
+    /// Fixed TCP HOME position (m)
+    pub home: [f64; 3],
+    pub task: TaskConfig,
+    pub scene: SceneConfig,
+    pub physics: PhysicsConfig,
+    /// Joint torque controller used with physics on
+    pub control: ControllerParams,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArmConfig {
+    /// Take the whole arm from a URDF file; the keys below are then ignored, except `base`
+    pub urdf: Option<UrdfSource>,
+    /// Where the arm is mounted in the world
+    pub base: BaseConfig,
+    pub link1: LinkConfig,
+    pub link2: LinkConfig,
+    /// Base yaw joint
+    pub yaw: JointConfig,
+    /// Turns the base cabling allows either way, instead of the yaw `min_deg`/`max_deg`
+    pub cable_wrap_turns: Option<f64>,
+    /// TCP offset from the tool flange, in the flange frame (m)
+    pub tcp: [f64; 3],
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UrdfSource {
+    /// Relative paths are resolved against the config file's directory
+    pub path: PathBuf,
+    /// Link whose origin is the TCP; may be omitted if the tree has a single leaf
+    pub tip: Option<String>,
+}
+
+/// Base mounting pose, written like a URDF `<origin>`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BaseConfig {
+    /// Base origin in the world (m)
+    pub xyz: [f64; 3],
+    /// Fixed-axis roll, pitch, yaw (rad), e.g. `[3.14159, 0, 0]` for a ceiling mount
+    pub rpy: [f64; 3],
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LinkConfig {
+    pub length_m: f64,
+    pub mass_kg: f64,
+    /// Joint driving this link
+    pub joint: JointConfig,
+}
+
+/// Joint limits (model angles) and encoder convention in file units; absent limits are unlimited.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JointConfig {
+    pub min_deg: Option<f64>,
+    pub max_deg: Option<f64>,
+    pub max_vel_rad_s: Option<f64>,
+    pub max_acc_rad_s2: Option<f64>,
+    /// Model angle at which the encoder reads zero
+    pub zero_offset_deg: f64,
+    /// Encoder counts opposite to the model direction
+    pub inverted: bool,
+    /// Motor and gearbox; ideal (no inertia, friction, play or torque limit) when absent
+    pub actuator: ActuatorConfig,
+}
+
+/// Joint drive in file units. Rotor inertia and torque limit are motor-side datasheet
+/// values; friction and backlash are measured at the joint.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActuatorConfig {
+    /// Motor turns per joint turn
+    pub gear_ratio: f64,
+    pub rotor_inertia_kgm2: f64,
+    pub viscous_nm_s_per_rad: f64,
+    /// Sliding friction (N m)
+    pub coulomb_nm: f64,
+    /// Breakaway friction of a resting joint (N m)
+    pub static_nm: f64,
+    /// Total free play of the gear train
+    pub backlash_deg: f64,
+    /// Peak motor torque (N m); unlimited when absent
+    pub max_motor_torque_nm: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TaskConfig {
+    pub start: [f64; 3],
+    pub goal: [f64; 3],
+    /// TCP cruise speed for all segments (m/s)
+    pub ee_speed_mps: f64,
+    /// TCP acceleration into and out of the cruise speed (m/s^2)
+    pub ee_accel_mps2: f64,
+    pub pick_duration_s: f64,
+    pub place_duration_s: f64,
+    /// Wait at HOME before the cycle repeats
+    pub reset_wait_s: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SceneConfig {
+    pub ball: BallConfig,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BallConfig {
+    /// Ball radius (m); scaled from the arm reach when absent
+    pub radius_m: Option<f64>,
+    /// Mass added to the tool while the ball is held (kg)
+    pub mass_kg: f64,
+}
+
+impl Default for BallConfig {
+    fn default() -> Self {
+        Self {
+            radius_m: None,
+            mass_kg: 1.0,
+        }
+    }
+}
+
+/// Forward-dynamics simulation used when physics is switched on in the viewer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PhysicsConfig {
+    /// Fixed physics step rate (Hz)
+    pub rate_hz: f64,
+    pub integrator: Integrator,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        let p = PhysicsParams::default();
+        Self {
+            rate_hz: p.rate_hz,
+            integrator: p.integrator,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    pub fn params(&self) -> PhysicsParams {
+        PhysicsParams {
+            rate_hz: self.rate_hz,
+            integrator: self.integrator,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            arm: ArmConfig::default(),
+            home: [2.0, 2.0, 2.0],
+            task: TaskConfig::default(),
+            scene: SceneConfig::default(),
+            physics: PhysicsConfig::default(),
+            control: ControllerParams::default(),
+        }
+    }
+}
+
+impl Default for ArmConfig {
+    fn default() -> Self {
+        Self {
+            urdf: None,
+            base: BaseConfig::default(),
+            link1: LinkConfig {
+                length_m: 3.0,
+                mass_kg: 2.0,
+                joint: JointConfig::limited(-20.0, 200.0, 2.0, 8.0),
+            },
+            link2: LinkConfig {
+                length_m: 2.6,
+                mass_kg: 1.6,
+                joint: JointConfig::limited(-165.0, 165.0, 2.5, 10.0),
+            },
+            // One and a half turns either way of base cabling
+            yaw: JointConfig::limited(-540.0, 540.0, 2.0, 8.0),
+            cable_wrap_turns: None,
+            // Suction cup face: 0.28 m tube + 0.06 m cup past the flange, along link 2
+            tcp: [0.34, 0.0, 0.0],
+        }
+    }
+}
+
+impl Default for ActuatorConfig {
+    fn default() -> Self {
+        let p = ActuatorParams::IDEAL;
+        Self {
+            gear_ratio: p.gear_ratio,
+            rotor_inertia_kgm2: p.rotor_inertia,
+            viscous_nm_s_per_rad: p.viscous_nm_s_per_rad,
+            coulomb_nm: p.coulomb_nm,
+            static_nm: p.static_nm,
+            backlash_deg: p.backlash_rad.to_degrees(),
+            max_motor_torque_nm: None,
+        }
+    }
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        let p = LinkParams::default();
+        Self {
+            length_m: p.length_m,
+            mass_kg: p.mass_kg,
+            joint: JointConfig::default(),
+        }
+    }
+}
+
+impl Default for TaskConfig {
+    fn default() -> Self {
+        Self {
+            start: [1.0, 2.0, 1.0],
+            goal: [2.0, 3.0, 2.0],
+            ee_speed_mps: 1.75,
+            ee_accel_mps2: 3.5,
+            pick_duration_s: 0.45,
+            place_duration_s: 0.35,
+            reset_wait_s: 1.5,
+        }
+    }
+}
+
+impl JointConfig {
+    fn limited(min_deg: f64, max_deg: f64, max_vel_rad_s: f64, max_acc_rad_s2: f64) -> Self {
+        Self {
+            min_deg: Some(min_deg),
+            max_deg: Some(max_deg),
+            max_vel_rad_s: Some(max_vel_rad_s),
+            max_acc_rad_s2: Some(max_acc_rad_s2),
+            ..Default::default()
+        }
+    }
+
+    pub fn limits(&self) -> JointLimits {
+        let d = JointLimits::default();
+        JointLimits {
+            min_rad: self.min_deg.map_or(d.min_rad, f64::to_radians),
+            max_rad: self.max_deg.map_or(d.max_rad, f64::to_radians),
+            max_vel_rad_s: self.max_vel_rad_s.unwrap_or(d.max_vel_rad_s),
+            max_acc_rad_s2: self.max_acc_rad_s2.unwrap_or(d.max_acc_rad_s2),
+        }
+    }
+
+    pub fn convention(&self) -> JointConvention {
+        JointConvention {
+            zero_offset_rad: self.zero_offset_deg.to_radians(),
+            inverted: self.inverted,
+        }
+    }
+
+    fn check(&self, name: &str, errors: &mut Vec<String>) {
+        if let (Some(lo), Some(hi)) = (self.min_deg, self.max_deg) {
+            if lo >= hi {
+                errors.push(format!("{name}: min_deg ({lo}) must be below max_deg ({hi})"));
+            }
+        }
+        for (field, v) in [("max_vel_rad_s", self.max_vel_rad_s), ("max_acc_rad_s2", self.max_acc_rad_s2)] {
+            if v.is_some_and(|v| !positive(v)) {
+                errors.push(format!("{name}.{field} must be positive"));
+            }
+        }
+        if !self.zero_offset_deg.is_finite() {
+            errors.push(format!("{name}.zero_offset_deg must be finite"));
+        }
+        self.actuator.check(&format!("{name}.actuator"), errors);
+    }
+}
+
+impl ActuatorConfig {
+    pub fn params(&self) -> ActuatorParams {
+        ActuatorParams {
+            gear_ratio: self.gear_ratio,
+            rotor_inertia: self.rotor_inertia_kgm2,
+            viscous_nm_s_per_rad: self.viscous_nm_s_per_rad,
+            coulomb_nm: self.coulomb_nm,
+            static_nm: self.static_nm,
+            backlash_rad: self.backlash_deg.to_radians(),
+            max_motor_torque_nm: self.max_motor_torque_nm.unwrap_or(f64::INFINITY),
+        }
+    }
+
+    fn check(&self, name: &str, errors: &mut Vec<String>) {
+        if !positive(self.gear_ratio) {
+            errors.push(format!("{name}.gear_ratio must be positive (got {})", self.gear_ratio));
+        }
+        for (field, v) in [
+            ("rotor_inertia_kgm2", self.rotor_inertia_kgm2),
+            ("viscous_nm_s_per_rad", self.viscous_nm_s_per_rad),
+            ("coulomb_nm", self.coulomb_nm),
+            ("static_nm", self.static_nm),
+            ("backlash_deg", self.backlash_deg),
+        ] {
+            if !non_negative(v) {
+                errors.push(format!("{name}.{field} must not be negative (got {v})"));
+            }
+        }
+        if self.max_motor_torque_nm.is_some_and(|t| !positive(t)) {
+            errors.push(format!("{name}.max_motor_torque_nm must be positive"));
+        }
+    }
+}
+
+impl LinkConfig {
+    pub fn params(&self) -> LinkParams {
+        let mut p = LinkParams {
+            length_m: self.length_m,
+            mass_kg: self.mass_kg,
+            limits: self.joint.limits(),
+            ..Default::default()
+        };
+        p.recompute_inertia();
+        p
+    }
+}
+
+impl TaskConfig {
+    pub fn speed_profile(&self) -> SpeedProfile {
+        SpeedProfile {
+            speed_mps: self.ee_speed_mps,
+            accel_mps2: self.ee_accel_mps2,
+        }
+    }
+}
+
+impl BaseConfig {
+    pub fn transform(&self) -> Transform {
+        Transform::from_xyz_rpy(self.xyz, self.rpy)
+    }
+}
+
+impl ArmConfig {
+    /// Encoder conventions of yaw, shoulder and elbow; these apply to URDF arms too.
+    pub fn joint_conventions(&self) -> [JointConvention; 3] {
+        [self.yaw.convention(), self.link1.joint.convention(), self.link2.joint.convention()]
+    }
+
+    /// Drives of yaw, shoulder and elbow; these apply to URDF arms too.
+    pub fn actuators(&self) -> [ActuatorParams; 3] {
+        [&self.yaw, &self.link1.joint, &self.link2.joint].map(|j| j.actuator.params())
+    }
+
+    pub fn build(&self) -> Result<RobotArm, UrdfError> {
+        if let Some(src) = &self.urdf {
+            let arm = UrdfModel::load(&src.path)?.to_robot_arm(src.tip.as_deref())?;
+            // The URDF root sits at the configured base; its own root-to-shoulder offset follows
+            let base = self.base.transform() * *arm.base();
+            return Ok(arm
+                .with_base(base)
+                .with_joint_conventions(self.joint_conventions())
+                .with_actuators(self.actuators()));
+        }
+
+        let mut arm = RobotArm::new(self.link1.params(), self.link2.params()).with_yaw_limits(self.yaw.limits());
+        if let Some(turns) = self.cable_wrap_turns {
+            arm = arm.with_cable_wrap(turns);
+        }
+        let [x, y, z] = self.tcp;
+        Ok(arm
+            .with_base(self.base.transform())
+            .with_tcp(Transform::from_translation(x, y, z))
+            .with_joint_conventions(self.joint_conventions())
+            .with_actuators(self.actuators()))
+    }
+}
+
+/// Why a configuration file could not be used.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io { path: PathBuf, source: std::io::Error },
+    Parse { path: PathBuf, message: String },
+    /// The file parsed but describes an unusable setup; one entry per problem
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => write!(f, "cannot read {}: {source}", path.display()),
+            ConfigError::Parse { path, message } => write!(f, "cannot parse {}: {message}", path.display()),
+            ConfigError::Invalid(errors) => {
+                write!(f, "invalid configuration:")?;
+                for e in errors {
+                    write!(f, "\n  - {e}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl SimConfig {
+    /// Read and validate `path`; `.json` files are parsed as JSON, anything else as TOML.
+    /// A `.urdf` file gives the default task and scene with that arm.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let has_ext = |ext: &str| path.extension().is_some_and(|e| e.eq_ignore_ascii_case(ext));
+        if has_ext("urdf") {
+            let mut config = Self::default();
+            config.arm.urdf = Some(UrdfSource {
+                path: path.to_path_buf(),
+                tip: None,
+            });
+            config.validate()?;
+            return Ok(config);
+        }
+
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        // The typed parse reports bad keys and values with their position in the file;
+        // the values themselves are then taken over the shipped defaults
+        let parsed = if has_ext("json") {
+            serde_json::from_str::<SimConfig>(&text)
+                .and_then(|_| serde_json::from_str::<serde_json::Value>(&text))
+                .map_err(|e| e.to_string())
+        } else {
+            toml::from_str::<SimConfig>(&text)
+                .and_then(|_| toml::from_str::<serde_json::Value>(&text))
+                .map_err(|e| e.to_string())
+        };
+        let mut config = parsed.and_then(Self::over_defaults).map_err(|message| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message,
+        })?;
+        if let (Some(src), Some(dir)) = (config.arm.urdf.as_mut(), path.parent()) {
+            src.path = dir.join(&src.path);
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Deserialize the file contents `file` over `SimConfig::default()`, merging tables key
+    /// by key at every depth. A plain `#[serde(default)]` would instead reset the keys a
+    /// table leaves out to that struct's own `Default`, not to the shipped arm.
+    fn over_defaults(file: serde_json::Value) -> Result<Self, String> {
+        let mut merged = serde_json::to_value(Self::default()).map_err(|e| e.to_string())?;
+        // A cable wrap takes the place of the default yaw limits; given both, validate() complains
+        if file.pointer("/arm/cable_wrap_turns").is_some() {
+            if let Some(yaw) = merged.pointer_mut("/arm/yaw").and_then(serde_json::Value::as_object_mut) {
+                yaw.remove("min_deg");
+                yaw.remove("max_deg");
+            }
+        }
+        merge_tables(&mut merged, file);
+        serde_json::from_value(merged).map_err(|e| e.to_string())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("SimConfig serializes to TOML")
+    }
+
+    pub fn home(&self) -> Vec3 {
+        self.home.into()
+    }
+
+    pub fn start(&self) -> Vec3 {
+        self.task.start.into()
+    }
+
+    pub fn goal(&self) -> Vec3 {
+        self.task.goal.into()
+    }
+
+    /// Ball radius, defaulting to 3 % of the reach within [0.06, 0.16] m.
+    pub fn ball_radius(&self, arm: &RobotArm) -> f64 {
+        self.scene
+            .ball
+            .radius_m
+            .unwrap_or_else(|| (0.03 * arm.max_reach()).clamp(0.06, 0.16))
+    }
+
+    /// The held ball as a solid sphere touching the TCP.
+    pub fn ball_payload(&self, arm: &RobotArm) -> Payload {
+        Payload::solid_sphere(self.scene.ball.mass_kg, self.ball_radius(arm))
+    }
+
+    /// Collect every problem at once so a bad file can be fixed in one pass.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+
+        for (name, link) in [("arm.link1", &self.arm.link1), ("arm.link2", &self.arm.link2)] {
+            if !positive(link.length_m) {
+                errors.push(format!("{name}.length_m must be positive (got {})", link.length_m));
+            }
+            if !non_negative(link.mass_kg) {
+                errors.push(format!("{name}.mass_kg must not be negative (got {})", link.mass_kg));
+            }
+            link.joint.check(&format!("{name}.joint"), &mut errors);
+        }
+        self.arm.yaw.check("arm.yaw", &mut errors);
+        let base = &self.arm.base;
+        if !base.xyz.iter().chain(&base.rpy).all(|v| v.is_finite()) {
+            errors.push(format!("arm.base must be finite (got xyz {:?}, rpy {:?})", base.xyz, base.rpy));
+        }
+        if let Some(turns) = self.arm.cable_wrap_turns {
+            if !positive(turns) {
+                errors.push("arm.cable_wrap_turns must be positive".to_string());
+            }
+            if self.arm.yaw.min_deg.is_some() || self.arm.yaw.max_deg.is_some() {
+                errors.push("arm.cable_wrap_turns and arm.yaw.min_deg/max_deg both limit the yaw; set only one".to_string());
+            }
+        }
+        if !positive(self.physics.rate_hz) {
+            errors.push(format!("physics.rate_hz must be positive (got {})", self.physics.rate_hz));
+        }
+        let pid = &self.control.pid;
+        let ct = &self.control.computed_torque;
+        for (name, gains) in [
+            ("control.pid.kp", pid.kp),
+            ("control.pid.ki", pid.ki),
+            ("control.pid.kd", pid.kd),
+            ("control.pid.integral_limit", pid.integral_limit),
+            ("control.computed_torque.kp", ct.kp),
+            ("control.computed_torque.kd", ct.kd),
+        ] {
+            if !gains.iter().all(|&g| non_negative(g)) {
+                errors.push(format!("{name} must not be negative (got {gains:?})"));
+            }
+        }
+
+        let t = &self.task;
+        if !positive(t.ee_speed_mps) {
+            errors.push(format!("task.ee_speed_mps must be positive (got {})", t.ee_speed_mps));
+        }
+        if !positive(t.ee_accel_mps2) {
+            errors.push(format!("task.ee_accel_mps2 must be positive (got {})", t.ee_accel_mps2));
+        }
+        for (name, v) in [
+            ("task.pick_duration_s", t.pick_duration_s),
+            ("task.place_duration_s", t.place_duration_s),
+            ("task.reset_wait_s", t.reset_wait_s),
+        ] {
+            if !non_negative(v) {
+                errors.push(format!("{name} must not be negative (got {v})"));
+            }
+        }
+        if self.scene.ball.radius_m.is_some_and(|r| !positive(r)) {
+            errors.push("scene.ball.radius_m must be positive".to_string());
+        }
+        if !non_negative(self.scene.ball.mass_kg) {
+            errors.push(format!("scene.ball.mass_kg must not be negative (got {})", self.scene.ball.mass_kg));
+        }
+
+        // Reachability only means something once the arm itself is valid
+        if errors.is_empty() {
+            let arm = match self.arm.build() {
+                Ok(arm) => arm,
+                Err(e) => return Err(ConfigError::Invalid(vec![format!("arm.urdf: {e}")])),
+            };
+            for (name, p) in [("home", self.home), ("task.start", t.start), ("task.goal", t.goal)] {
+                let ik = arm.solve_ik_preferred(p.into(), false);
+                if !ik.reachable {
+                    errors.push(format!("{name} {:?} is not reachable: {}", p, ik.message));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+}
+
+// NaN fails both checks
+fn positive(v: f64) -> bool {
+    v > 0.0 && v.is_finite()
+}
+
+fn non_negative(v: f64) -> bool {
+    v >= 0.0 && v.is_finite()
+}
+
+/// Overwrite `base` with `over`, descending into tables present in both.
+fn merge_tables(base: &mut serde_json::Value, over: serde_json::Value) {
+    match (base, over) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(over)) => {
+            for (key, value) in over {
+                merge_tables(base.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
@@ -0,0 +1,16 @@
+//! 3-DOF manipulator kinematics, motion and workspace analysis.
+//!
+//! The library is independent of the viewer: `robot` holds the arm model
+//! (FK/IK, Jacobians, DH chains), `sim` the trajectory runners and
+//! `planning` the workspace analysis. All geometry is f64 (`robot::Vec3`).
+//! The raylib viewer binary is built with the `viewer` feature (on by default);
+//! `config` adds TOML/JSON loading of the arm and scene.
+
+#[cfg(feature = "config")]
+pub mod config;
+pub mod planning;
+pub mod robot;
+pub mod sim;
+
+pub use robot::{JointAngles, JointLimits, LinkParams, RobotArm, Transform, Vec3};
+pub use sim::LinearTrajectory;
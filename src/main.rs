@@ -1,13 +1,18 @@
 use raylib::prelude::*;
 
-mod robot;
-mod sim;
 mod ui;
 mod render;
 
-use robot::{JointAngles, LinkParams, RobotArm};
-use sim::LinearTrajectory;
-use ui::{OverlayAction, OverlayState, OverlayStatus, UiInput};
+use manipulator3d::config::SimConfig;
+use manipulator3d::{planning, robot, sim};
+use planning::{WorkspaceMap, WorkspaceParams};
+use render::to_vector3;
+use robot::{JointAngles, RobotArm, SingularityThresholds, TorqueStats, Vec3};
+use sim::{
+    JointController, JointReference, JointState, LinearTrajectory, PhysicsSim, ResolvedRateController, ResolvedRateParams,
+    SegmentPlan, SingularityStrategy,
+};
+use ui::{OverlayAction, OverlayState, OverlayStatus, RuntimeStatus, UiInput};
 
 use raylib::core::drawing::{RaylibDraw, RaylibDraw3D, RaylibMode3DExt};
 use raylib::core::texture::RaylibTexture2D;
@@ -24,6 +29,23 @@ enum Phase {
     Error,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ControlMode {
+    /// Joint angles snap to IK of the target every frame
+    IkTeleport,
+    /// Cartesian velocity -> joint rates via the damped Jacobian, integrated
+    ResolvedRate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum WorkspaceView {
+    Off,
+    /// TCP positions from a joint-space sweep, colored by dexterity
+    PointCloud,
+    /// One horizontal layer of the voxel map, colored by dexterity
+    HeatmapSlice,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum BallState {
     AtStart,
@@ -80,11 +102,29 @@ fn v3_norm(a: Vector3) -> Vector3 {
     }
 }
 
-fn segment_duration(from: Vector3, to: Vector3, speed_mps: f32) -> f32 {
-    let dist = v3_len(v3_sub(to, from));
-    let s = speed_mps.max(1e-3);
-    // clamp so very short/very long segments still look reasonable
-    clampf(dist / s, 0.35, 8.0)
+/// How the current segment was planned and timed.
+#[derive(Copy, Clone, Debug)]
+struct Segment {
+    plan: SegmentPlan,
+    /// False when even `MAX_TIME_STRETCH` times slower breaks a joint rate or acceleration limit
+    within_limits: bool,
+}
+
+// Plan from -> to under the singularity strategy and load it into the trajectory,
+// timed so the joints (starting at q_start) stay within their speed/acceleration limits
+fn begin_segment(
+    arm: &RobotArm,
+    traj: &mut LinearTrajectory,
+    q_start: JointAngles,
+    from: Vec3,
+    to: Vec3,
+    strategy: SingularityStrategy,
+    th: &SingularityThresholds,
+) -> Result<Segment, String> {
+    let plan = sim::plan_segment(arm, from, to, strategy, th)?;
+    traj.reset_via(from, plan.via(), to);
+    let within_limits = traj.fit_joint_limits(arm, q_start);
+    Ok(Segment { plan, within_limits })
 }
 
 fn load_best_ui_font(rl: &mut RaylibHandle, thread: &RaylibThread, px: i32) -> UiFont {
@@ -133,6 +173,18 @@ fn update_zoom(rl: &RaylibHandle, cam: &mut Camera3D) {
 }
 
 fn main() {
+    // Optional arm/scene file: `manipulator3d [config.toml | config.json | arm.urdf]`
+    let config = match std::env::args().nth(1) {
+        Some(path) => match SimConfig::load(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(2);
+            }
+        },
+        None => SimConfig::default(),
+    };
+
     let (mut rl, thread) = raylib::init()
         .size(1280, 720)
         .title("Manipulator3D - 3DOF IK Pick&Place (Rust)")
@@ -143,35 +195,20 @@ fn main() {
     rl.set_window_min_size(960, 540);
     rl.set_target_fps(60);
 
-    // --- Arm parameters ---
-    let mut link1 = LinkParams {
-        length_m: 3.0,
-        mass_kg: 2.0,
-        ..Default::default()
-    };
-    link1.recompute_inertia();
-
-    let mut link2 = LinkParams {
-        length_m: 2.6,
-        mass_kg: 1.6,
-        ..Default::default()
-    };
-    link2.recompute_inertia();
-
-    let arm = RobotArm::new(link1, link2);
+    let arm = config.arm.build().expect("arm was checked when the config was loaded");
 
-    // Fixed EE HOME position
-    let home_ee = Vector3 { x: 2.0, y: 2.0, z: 2.0 };
+    // Fixed TCP HOME position
+    let home_ee = config.home();
 
     // Defaults
-    let mut start = Vector3 { x: 1.0, y: 2.0, z: 1.0 };
-    let mut goal  = Vector3 { x: 2.0, y: 3.0, z: 2.0 };
+    let mut start = config.start();
+    let mut goal = config.goal();
 
     // UI textboxes
-    let mut overlay = OverlayState::new("1 2 1", "2 3 2");
+    let mut overlay = OverlayState::new(home_ee, start, goal);
 
     // Camera
-    let reach = arm.max_reach();
+    let reach = arm.max_reach() as f32;
     let target = Vector3 { x: 0.0, y: 0.0, z: 0.35 * reach };
     let up     = Vector3 { x: 0.0, y: 0.0, z: 1.0 };
     let pos    = Vector3 { x: 1.10 * reach, y: -1.15 * reach, z: 0.85 * reach };
@@ -181,49 +218,84 @@ fn main() {
     let ui_font = load_best_ui_font(&mut rl, &thread, 22);
 
     // Timing (fixed dwell times)
-    let pick_duration = 0.45_f32;
-    let place_duration = 0.35_f32;
-    let reset_wait_total = 1.5_f32;
-
-    // Motion: use constant end-effector speed (m/s) for all segments
-    // Increase this number if you want the whole motion faster.
-    let ee_speed_mps = 1.75_f32;
+    let pick_duration = config.task.pick_duration_s;
+    let place_duration = config.task.place_duration_s;
+    let reset_wait_total = config.task.reset_wait_s;
 
     // Runtime state
     let mut paused = true; // start paused (user enters start/goal)
     let mut phase = Phase::WaitAtHomeReset;
 
-    let mut timer: f32 = 0.0;
+    let mut timer: f64 = 0.0;
 
     // Ball
-    let ball_radius = clampf(0.03 * reach, 0.06, 0.16);
+    let ball_radius = config.ball_radius(&arm) as f32;
+    let ball_payload = config.ball_payload(&arm);
     let mut ball_state = BallState::AtStart;
     let mut ball_pos = start;
 
     // EE control
     let mut target_ee = home_ee;
-    let mut qcmd = JointAngles::default();
+    let mut qcmd = arm.solve_ik_preferred(home_ee, false).q;
+    let mut qdot = [0.0_f64; 3];
+    // Inverse-dynamics torques of the commanded motion, with peak/RMS over the current
+    // pick-and-place cycle and the last completed one
+    let mut torque = [0.0_f64; 3];
+    let mut torque_stats = TorqueStats::default();
+    let mut last_cycle_stats: Option<TorqueStats> = None;
+    let mut max_step = (0_usize, 0.0_f64);
+    let mut branch_switched = false;
+
+    // Control mode (F2 toggles)
+    let mut control_mode = ControlMode::IkTeleport;
+    let mut rate_ctrl = ResolvedRateController::new(ResolvedRateParams::default());
+
+    // Forward dynamics (F7 toggles, F8 switches integrator): joint torques from the
+    // controller (F9 cycles) track the command instead of the arm snapping to it
+    let mut physics = PhysicsSim::new(config.physics.params(), qcmd);
+    // The arm as the dynamics see it: carries the ball while it is held
+    let mut plant = arm.clone();
+    let mut physics_on = false;
+    let mut controller = JointController::new(config.control);
+
+    // Trajectory: trapezoidal TCP speed along each leg
+    let mut traj = LinearTrajectory::new(config.task.speed_profile());
+
+    // Singularity handling along paths (F3 cycles the strategy)
+    let mut singularity_strategy = SingularityStrategy::SlowDown;
+    let singularity_th = SingularityThresholds::default();
+    // How the current segment was planned (shows when a detour had to fall back to slowing down)
+    let mut segment = Segment {
+        plan: SegmentPlan::Straight,
+        within_limits: true,
+    };
 
-    // Trajectory
-    let mut traj = LinearTrajectory::default();
+    // Workspace analysis (F4 cycles the view, PgUp/PgDn move the slice, F5 exports voxels)
+    let workspace_params = WorkspaceParams::default();
+    let mut workspace_view = WorkspaceView::Off;
+    let mut workspace_map: Option<WorkspaceMap> = None;
+    let mut workspace_cloud: Vec<(Vector3, f32)> = Vec::new();
+    let mut workspace_layer = 0_usize;
+    let mut export_note = String::new();
 
     let mut runtime_error: Option<String> = None;
 
     // Start/restart a new run using the current start/goal
-    let start_simulation = |start_p: Vector3,
-                            goal_p: Vector3,
+    let start_simulation = |start_p: Vec3,
+                            goal_p: Vec3,
                             phase_ref: &mut Phase,
                             paused_ref: &mut bool,
-                            timer_ref: &mut f32,
-                            target_ee_ref: &mut Vector3,
+                            timer_ref: &mut f64,
+                            target_ee_ref: &mut Vec3,
                             qcmd_ref: &mut JointAngles,
                             traj_ref: &mut LinearTrajectory,
                             ball_state_ref: &mut BallState,
                             runtime_error_ref: &mut Option<String>,
-                            ee_speed_mps: f32| {
-        let ik_home = arm.solve_ik(home_ee, false);
-        let ik_start = arm.solve_ik(start_p, false);
-        let ik_goal = arm.solve_ik(goal_p, false);
+                            segment_ref: &mut Segment,
+                            strategy: SingularityStrategy| {
+        let ik_home = arm.solve_ik_preferred(home_ee, false);
+        let ik_start = arm.solve_ik_preferred(start_p, false);
+        let ik_goal = arm.solve_ik_preferred(goal_p, false);
 
         if !ik_home.reachable || !ik_start.reachable || !ik_goal.reachable {
             *phase_ref = Phase::Error;
@@ -241,6 +313,17 @@ fn main() {
             return;
         }
 
+        // Check every segment of the cycle up front so Stop fails before moving
+        let segments = [(home_ee, start_p, "HOME -> START"), (start_p, goal_p, "START -> GOAL"), (goal_p, home_ee, "GOAL -> HOME")];
+        for (from, to, name) in segments {
+            if let Err(e) = sim::plan_segment(&arm, from, to, strategy, &singularity_th) {
+                *phase_ref = Phase::Error;
+                *paused_ref = true;
+                *runtime_error_ref = Some(format!("{name}: {e}"));
+                return;
+            }
+        }
+
         *runtime_error_ref = None;
 
         // Initialize at HOME pose
@@ -252,8 +335,15 @@ fn main() {
 
         *timer_ref = 0.0;
 
-        let dur = segment_duration(home_ee, start_p, ee_speed_mps);
-        traj_ref.reset(home_ee, start_p, dur);
+        match begin_segment(&arm, traj_ref, ik_home.q, home_ee, start_p, strategy, &singularity_th) {
+            Ok(planned) => *segment_ref = planned,
+            Err(e) => {
+                *phase_ref = Phase::Error;
+                *paused_ref = true;
+                *runtime_error_ref = Some(e);
+                return;
+            }
+        }
 
         *phase_ref = Phase::MoveHomeToStart;
         *paused_ref = false;
@@ -263,6 +353,74 @@ fn main() {
         if rl.is_key_pressed(KeyboardKey::KEY_F11) {
             rl.toggle_fullscreen();
         }
+        if rl.is_key_pressed(KeyboardKey::KEY_F2) {
+            control_mode = match control_mode {
+                ControlMode::IkTeleport => ControlMode::ResolvedRate,
+                ControlMode::ResolvedRate => ControlMode::IkTeleport,
+            };
+            rate_ctrl.reset();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F7) {
+            physics_on = !physics_on;
+            physics.reset(JointState { q: qcmd, qd: qdot });
+            controller.reset();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F8) {
+            physics.params.integrator = physics.params.integrator.next();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F9) {
+            controller.params.kind = controller.params.kind.next();
+            controller.reset();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F3) {
+            singularity_strategy = singularity_strategy.next();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F4) {
+            workspace_view = match workspace_view {
+                WorkspaceView::Off => WorkspaceView::PointCloud,
+                WorkspaceView::PointCloud => WorkspaceView::HeatmapSlice,
+                WorkspaceView::HeatmapSlice => WorkspaceView::Off,
+            };
+        }
+        let export_workspace = rl.is_key_pressed(KeyboardKey::KEY_F5);
+        if rl.is_key_pressed(KeyboardKey::KEY_F6) {
+            export_note = match arm
+                .save_urdf("manipulator3d", "manipulator3d.urdf")
+                .and_then(|()| arm.save_mjcf("manipulator3d", "manipulator3d.xml"))
+            {
+                Ok(()) => "Saved manipulator3d.urdf / .xml".to_string(),
+                Err(e) => format!("Export failed: {e}"),
+            };
+        }
+
+        // Sample the workspace once, the first time it is needed
+        if (workspace_view != WorkspaceView::Off || export_workspace) && workspace_map.is_none() {
+            let map = WorkspaceMap::build(&arm, &workspace_params);
+            let points = planning::sample_joint_space(&arm, &workspace_params);
+            let w_max = points.iter().fold(1e-6_f64, |m, p| m.max(p.manipulability));
+            workspace_cloud = points
+                .iter()
+                .map(|p| (to_vector3(p.position), (p.manipulability / w_max) as f32))
+                .collect();
+            workspace_layer = map.layer_at(home_ee.z).unwrap_or(0);
+            workspace_map = Some(map);
+        }
+
+        if let Some(map) = &workspace_map {
+            if rl.is_key_pressed(KeyboardKey::KEY_PAGE_UP) {
+                workspace_layer = (workspace_layer + 1).min(map.dims()[2] - 1);
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_PAGE_DOWN) {
+                workspace_layer = workspace_layer.saturating_sub(1);
+            }
+            if export_workspace {
+                let path = "workspace_voxels.txt";
+                export_note = match map.save_voxels(path) {
+                    Ok(()) => format!("Saved {path}"),
+                    Err(e) => format!("Export failed: {e}"),
+                };
+            }
+        }
 
         let screen_w = rl.get_screen_width();
         let screen_h = rl.get_screen_height();
@@ -294,14 +452,20 @@ fn main() {
         };
 
         // Simulation dt
-        let dt = if paused { 0.0 } else { rl.get_frame_time() };
+        let dt = if paused { 0.0 } else { rl.get_frame_time() as f64 };
+
+        // Path time runs slower near singularities (Stop never runs a hazardous segment)
+        let path_scale = match singularity_strategy {
+            SingularityStrategy::Stop => 1.0,
+            _ => sim::slowdown_factor(&arm, qcmd, &singularity_th),
+        };
 
         if phase != Phase::Error {
             match phase {
                 Phase::MoveHomeToStart => {
                     ball_state = BallState::AtStart;
 
-                    traj.update(dt);
+                    traj.update(dt * path_scale);
                     target_ee = traj.position();
 
                     if traj.finished() {
@@ -319,14 +483,21 @@ fn main() {
                         ball_state = BallState::Attached;
                         timer = 0.0;
 
-                        let dur = segment_duration(start, goal, ee_speed_mps);
-                        traj.reset(start, goal, dur);
-
-                        phase = Phase::MoveStartToGoal;
+                        match begin_segment(&arm, &mut traj, qcmd, start, goal, singularity_strategy, &singularity_th) {
+                            Ok(planned) => {
+                                phase = Phase::MoveStartToGoal;
+                                segment = planned;
+                            }
+                            Err(e) => {
+                                phase = Phase::Error;
+                                paused = true;
+                                runtime_error = Some(e);
+                            }
+                        }
                     }
                 }
                 Phase::MoveStartToGoal => {
-                    traj.update(dt);
+                    traj.update(dt * path_scale);
                     target_ee = traj.position();
                     ball_state = BallState::Attached;
 
@@ -344,10 +515,17 @@ fn main() {
                         ball_state = BallState::AtGoal;
                         timer = 0.0;
 
-                        let dur = segment_duration(goal, home_ee, ee_speed_mps);
-                        traj.reset(goal, home_ee, dur);
-
-                        phase = Phase::ReturnGoalToHome;
+                        match begin_segment(&arm, &mut traj, qcmd, goal, home_ee, singularity_strategy, &singularity_th) {
+                            Ok(planned) => {
+                                phase = Phase::ReturnGoalToHome;
+                                segment = planned;
+                            }
+                            Err(e) => {
+                                phase = Phase::Error;
+                                paused = true;
+                                runtime_error = Some(e);
+                            }
+                        }
                     } else {
                         ball_state = BallState::Attached;
                     }
@@ -355,7 +533,7 @@ fn main() {
                 Phase::ReturnGoalToHome => {
                     ball_state = BallState::AtGoal;
 
-                    traj.update(dt);
+                    traj.update(dt * path_scale);
                     target_ee = traj.position();
 
                     timer += dt; // time since place
@@ -372,10 +550,19 @@ fn main() {
 
                         timer = 0.0;
 
-                        let dur = segment_duration(home_ee, start, ee_speed_mps);
-                        traj.reset(home_ee, start, dur);
-
-                        phase = Phase::MoveHomeToStart;
+                        match begin_segment(&arm, &mut traj, qcmd, home_ee, start, singularity_strategy, &singularity_th) {
+                            Ok(planned) => {
+                                phase = Phase::MoveHomeToStart;
+                                segment = planned;
+                                last_cycle_stats = Some(torque_stats);
+                                torque_stats = TorqueStats::default();
+                            }
+                            Err(e) => {
+                                phase = Phase::Error;
+                                paused = true;
+                                runtime_error = Some(e);
+                            }
+                        }
                     } else {
                         ball_state = BallState::AtGoal;
                     }
@@ -383,34 +570,81 @@ fn main() {
                 Phase::Error => {}
             }
 
-            // IK for current target
-            let ik_now = arm.solve_ik(target_ee, false);
-            if !ik_now.reachable {
-                phase = Phase::Error;
-                paused = true;
-                runtime_error = Some(ik_now.message);
-            } else {
-                qcmd = ik_now.q;
+            plant.set_payload((ball_state == BallState::Attached).then_some(ball_payload));
+
+            let q_prev = qcmd;
+            match control_mode {
+                ControlMode::IkTeleport => {
+                    // IK for current target, continuing from the last command
+                    let ik_now = arm.solve_ik_continuous(target_ee, qcmd);
+                    if !ik_now.reachable {
+                        phase = Phase::Error;
+                        paused = true;
+                        runtime_error = Some(ik_now.message);
+                    } else {
+                        qcmd = ik_now.q;
+                        branch_switched = ik_now.continuity.is_some_and(|c| c.branch_switched);
+                    }
+                }
+                ControlMode::ResolvedRate => {
+                    qcmd = rate_ctrl.track(&arm, qcmd, target_ee, traj.velocity() * path_scale, dt);
+                    branch_switched = false;
+                }
+            }
+            max_step = robot::max_joint_step(q_prev, qcmd);
+
+            if dt > 0.0 {
+                // Joint rates and accelerations of the path itself through J^-1, rather than
+                // differences of qcmd, which spike wherever a segment starts or stops
+                let v = traj.velocity() * path_scale;
+                let a = traj.acceleration() * (path_scale * path_scale);
+                let qddot = match arm.joint_rates(qcmd, v) {
+                    Some(rates) => {
+                        qdot = rates;
+                        arm.joint_accelerations(qcmd, qdot, a).unwrap_or([0.0; 3])
+                    }
+                    // Singular configuration: hold the last rates
+                    None => [0.0; 3],
+                };
+                if physics_on {
+                    // Reference over the frame, extrapolated back from the new command
+                    let reference = JointReference { q: qcmd, qd: qdot, qdd: qddot };
+                    let h = physics.step_size();
+                    let mut t = -dt;
+                    physics.advance(&plant, dt, |s| {
+                        let tau = controller.torque(&plant, &reference.at(t), s, h);
+                        t += h;
+                        tau
+                    });
+                    // What the motors delivered, after saturation
+                    torque = physics.applied_torque();
+                } else {
+                    torque = plant.inverse_dynamics(qcmd, qdot, qddot);
+                }
+                torque_stats.add(torque, dt);
             }
         }
 
-        // FK for render
-        let fk = arm.forward_kinematics(qcmd);
-
-        // Tool approach direction
-        let mut approach = v3_sub(fk.ee, fk.joint2);
-        let alen = v3_len(approach);
-        if alen > 1e-6 {
-            approach = v3_scale(approach, 1.0 / alen);
+        // Joint state on screen: simulated when physics is on, else the command
+        let (q_shown, qdot_shown) = if physics_on {
+            let s = physics.state();
+            (s.q, s.qd)
         } else {
-            approach = Vector3 { x: 1.0, y: 0.0, z: 0.0 };
-        }
+            (qcmd, qdot)
+        };
+
+        // FK for render
+        let fk = arm.forward_kinematics(q_shown);
 
-        // Ball position (always visible)
+        // Ball centre (always visible): held by the suction cup one radius out along the tool
+        // axis, waiting where the tool will pick it up at START, left where it was let go
         ball_pos = match ball_state {
-            BallState::AtStart => start,
-            BallState::AtGoal => goal,
-            BallState::Attached => v3_add(fk.ee, v3_scale(approach, 0.22)),
+            BallState::AtStart => {
+                let pick = arm.forward_kinematics(arm.solve_ik_preferred(start, false).q);
+                pick.tool.transform_point(ball_payload.com)
+            }
+            BallState::AtGoal => ball_pos,
+            BallState::Attached => fk.tool.transform_point(ball_payload.com),
         };
 
         // Render
@@ -428,18 +662,35 @@ fn main() {
             d3.draw_cylinder_ex(Vector3::zero(), Vector3 { x: 0.0, y: axis_len, z: 0.0 }, axis_r, axis_r, 12, Color::GREEN);
             d3.draw_cylinder_ex(Vector3::zero(), Vector3 { x: 0.0, y: 0.0, z: axis_len }, axis_r, axis_r, 12, Color::BLUE);
 
-            render::draw_robot_base_pedestal(&mut d3, Vector3::zero());
-            render::draw_robot_joint_housing(&mut d3, fk.base, 0.30);
-            render::draw_robot_joint_housing(&mut d3, fk.joint2, 0.24);
-            render::draw_robot_joint_housing(&mut d3, fk.ee, 0.18);
+            let base = to_vector3(fk.base);
+            let joint2 = to_vector3(fk.joint2);
+            let ee = to_vector3(fk.ee);
 
-            render::draw_tapered_link(&mut d3, fk.base, fk.joint2, 0.14, 0.12, Color::new(185, 185, 190, 255));
-            render::draw_tapered_link(&mut d3, fk.joint2, fk.ee, 0.12, 0.10, Color::new(170, 170, 175, 255));
+            render::draw_robot_base_pedestal(&mut d3, base, to_vector3(fk.joint_axis(0)));
+            render::draw_robot_joint_housing(&mut d3, base, 0.30);
+            render::draw_robot_joint_housing(&mut d3, joint2, 0.24);
+            render::draw_robot_joint_housing(&mut d3, ee, 0.18);
 
-            render::draw_suction_tool(&mut d3, fk.ee, approach);
+            render::draw_tapered_link(&mut d3, base, joint2, 0.14, 0.12, Color::new(185, 185, 190, 255));
+            render::draw_tapered_link(&mut d3, joint2, ee, 0.12, 0.10, Color::new(170, 170, 175, 255));
 
-            d3.draw_sphere(ball_pos, ball_radius, Color::RED);
-            d3.draw_sphere_wires(ball_pos, ball_radius * 1.02, 10, 10, Color::RAYWHITE);
+            render::draw_suction_tool(&mut d3, ee, to_vector3(fk.tcp), to_vector3(fk.approach()));
+
+            let ball = to_vector3(ball_pos);
+            d3.draw_sphere(ball, ball_radius, Color::RED);
+            d3.draw_sphere_wires(ball, ball_radius * 1.02, 10, 10, Color::RAYWHITE);
+
+            match (workspace_view, &workspace_map) {
+                (WorkspaceView::PointCloud, _) => render::draw_point_cloud(&mut d3, &workspace_cloud, 0.04),
+                (WorkspaceView::HeatmapSlice, Some(map)) => {
+                    let cells: Vec<(Vector3, f32)> = map
+                        .layer(workspace_layer)
+                        .map(|(p, s)| (to_vector3(p), map.dexterity(&s) as f32))
+                        .collect();
+                    render::draw_heatmap_slice(&mut d3, &cells, map.resolution() as f32);
+                }
+                _ => {}
+            }
         }
 
         // Overlay status
@@ -458,6 +709,65 @@ fn main() {
             error_text: runtime_error.as_deref(),
         };
 
+        let strategy_text = match segment.plan {
+            SegmentPlan::SlowDownFallback(hazard) if !traj.finished() => {
+                format!("Singularity: no detour around {}, slowing down", hazard.kind.label())
+            }
+            _ => format!("Singularity: {}", singularity_strategy.label()),
+        };
+        let physics_text = if physics_on {
+            format!("Physics: {} @ {:.0} Hz", physics.params.integrator.label(), physics.params.rate_hz)
+        } else {
+            "Physics: off (kinematic)".to_string()
+        };
+        let controller_text = if physics_on && physics.saturated() {
+            format!("Torque: {} (motor limit)", controller.params.kind.label())
+        } else {
+            format!("Torque: {}", controller.params.kind.label())
+        };
+        let payload_text = match plant.payload() {
+            Some(p) => format!("Payload: {:.2} kg held", p.mass_kg),
+            None => "Payload: none".to_string(),
+        };
+        let workspace_text = match (workspace_view, &workspace_map) {
+            (WorkspaceView::PointCloud, _) => format!("Workspace: {} joint samples", workspace_cloud.len()),
+            (WorkspaceView::HeatmapSlice, Some(map)) => format!(
+                "Workspace: z = {:.2} m, {:.0}% reachable",
+                map.center(0, 0, workspace_layer).z,
+                map.reachable_fraction() * 100.0
+            ),
+            _ => "Workspace: off".to_string(),
+        };
+        let rt = RuntimeStatus {
+            control_text: match control_mode {
+                ControlMode::IkTeleport => "Control: IK (snap to target)",
+                ControlMode::ResolvedRate => "Control: resolved-rate",
+            },
+            q: q_shown,
+            q_hardware: arm.to_hardware(q_shown),
+            qdot: qdot_shown,
+            torque,
+            torque_stats: last_cycle_stats.unwrap_or(torque_stats),
+            torque_stats_complete: last_cycle_stats.is_some(),
+            rate_limited: control_mode == ControlMode::ResolvedRate && rate_ctrl.saturated(),
+            path_over_limits: !traj.finished() && !segment.within_limits,
+            max_step,
+            branch_switched,
+            strategy_text: &strategy_text,
+            physics_text: &physics_text,
+            controller_text: &controller_text,
+            payload_text: &payload_text,
+            tracking_error: physics_on.then(|| (controller.tracking_error(), controller.peak_error())),
+            workspace_text: &workspace_text,
+            export_note: &export_note,
+            singularity: arm.singularity_proximity(q_shown).nearest(&singularity_th),
+        };
+
+        match &ui_font {
+            UiFont::Owned(f) => ui::draw_runtime_panel(&mut d, f, &rt, screen_w),
+            UiFont::Default(f) => ui::draw_runtime_panel(&mut d, f, &rt, screen_w),
+        }
+
         // Overlay + actions
         let action = match &ui_font {
             UiFont::Owned(f) => ui::draw_overlay_panel(&mut d, f, &arm, &mut overlay, &st, paused, &ui_input, screen_w, screen_h),
@@ -482,17 +792,25 @@ fn main() {
                     &mut traj,
                     &mut ball_state,
                     &mut runtime_error,
-                    ee_speed_mps,
+                    &mut segment,
+                    singularity_strategy,
                 );
+                rate_ctrl.reset();
+                qdot = [0.0; 3];
+                torque_stats = TorqueStats::default();
+                last_cycle_stats = None;
+                physics.reset(JointState { q: qcmd, qd: qdot });
+                controller.reset();
             }
         }
 
         // Footer help
+        let footer = "F11: fullscreen   F2: control   F3: singularity   F4: workspace   F5: export voxels   F6: export model   F7/F8/F9: physics / integrator / controller   PgUp/PgDn: slice   Wheel: zoom";
         match &ui_font {
             UiFont::Owned(f) => render::draw_text_small(
                 &mut d,
                 f,
-                "F11: fullscreen   Mouse Wheel: zoom",
+                footer,
                 12,
                 screen_h - 28,
                 18.0,
@@ -501,7 +819,7 @@ fn main() {
             UiFont::Default(f) => render::draw_text_small(
                 &mut d,
                 f,
-                "F11: fullscreen   Mouse Wheel: zoom",
+                footer,
                 12,
                 screen_h - 28,
                 18.0,
@@ -0,0 +1,3 @@
+pub mod workspace;
+
+pub use workspace::{sample_joint_space, VoxelSample, WorkspaceMap, WorkspaceParams, WorkspacePoint};
@@ -0,0 +1,328 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::robot::{FKResult, JointAngles, JointLimits, RobotArm, Vec3};
+
+#[derive(Copy, Clone, Debug)]
+pub struct WorkspaceParams {
+    /// Voxel edge length (m)
+    pub resolution_m: f64,
+    /// Height of the floor plane; no link point may go below it
+    pub floor_z: f64,
+    /// Samples per joint for `sample_joint_space`
+    pub joint_steps: usize,
+}
+
+impl Default for WorkspaceParams {
+    fn default() -> Self {
+        Self {
+            resolution_m: 0.25,
+            floor_z: 0.0,
+            joint_steps: 24,
+        }
+    }
+}
+
+/// Reachability of one voxel center.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct VoxelSample {
+    /// IK branches that respect joint limits and clear the floor (0 = unreachable)
+    pub solutions: u8,
+    /// Best Yoshikawa manipulability over those branches
+    pub manipulability: f64,
+}
+
+impl VoxelSample {
+    pub fn reachable(&self) -> bool {
+        self.solutions > 0
+    }
+}
+
+/// TCP position reached by one joint-space sample.
+#[derive(Copy, Clone, Debug)]
+pub struct WorkspacePoint {
+    pub position: Vec3,
+    pub q: JointAngles,
+    pub manipulability: f64,
+}
+
+/// Cartesian voxel grid of reachability and dexterity around the arm base.
+#[derive(Clone, Debug)]
+pub struct WorkspaceMap {
+    /// Minimum corner of the grid
+    origin: Vec3,
+    resolution: f64,
+    dims: [usize; 3],
+    cells: Vec<VoxelSample>,
+    max_manipulability: f64,
+}
+
+impl WorkspaceMap {
+    /// Solve IK at every voxel center within `max_reach` of the base and above the floor.
+    pub fn build(arm: &RobotArm, params: &WorkspaceParams) -> Self {
+        let res = params.resolution_m.max(0.01);
+        let reach = arm.max_reach();
+        let b = arm.base().translation;
+
+        let lo = Vec3 {
+            x: b.x - reach,
+            y: b.y - reach,
+            z: (b.z - reach).max(params.floor_z),
+        };
+        let hi_z = (b.z + reach).max(lo.z + res);
+        let n_xy = ((2.0 * reach) / res).ceil() as usize;
+        let dims = [n_xy, n_xy, ((hi_z - lo.z) / res).ceil() as usize];
+
+        let mut map = Self {
+            origin: lo,
+            resolution: res,
+            dims,
+            cells: vec![VoxelSample::default(); dims[0] * dims[1] * dims[2]],
+            max_manipulability: 0.0,
+        };
+
+        for k in 0..dims[2] {
+            for j in 0..dims[1] {
+                for i in 0..dims[0] {
+                    let sample = sample_point(arm, map.center(i, j, k), params.floor_z);
+                    map.max_manipulability = map.max_manipulability.max(sample.manipulability);
+                    let idx = map.index(i, j, k);
+                    map.cells[idx] = sample;
+                }
+            }
+        }
+        map
+    }
+
+    pub fn origin(&self) -> Vec3 { self.origin }
+    pub fn resolution(&self) -> f64 { self.resolution }
+    pub fn dims(&self) -> [usize; 3] { self.dims }
+    pub fn max_manipulability(&self) -> f64 { self.max_manipulability }
+
+    pub fn cell(&self, i: usize, j: usize, k: usize) -> VoxelSample {
+        self.cells[self.index(i, j, k)]
+    }
+
+    pub fn center(&self, i: usize, j: usize, k: usize) -> Vec3 {
+        Vec3 {
+            x: self.origin.x + (i as f64 + 0.5) * self.resolution,
+            y: self.origin.y + (j as f64 + 0.5) * self.resolution,
+            z: self.origin.z + (k as f64 + 0.5) * self.resolution,
+        }
+    }
+
+    /// Layer index containing height `z`, if inside the grid.
+    pub fn layer_at(&self, z: f64) -> Option<usize> {
+        let k = ((z - self.origin.z) / self.resolution).floor();
+        (k >= 0.0 && (k as usize) < self.dims[2]).then_some(k as usize)
+    }
+
+    /// Manipulability scaled by the best value in the map, in [0, 1].
+    pub fn dexterity(&self, sample: &VoxelSample) -> f64 {
+        if self.max_manipulability > 0.0 {
+            (sample.manipulability / self.max_manipulability).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Centers and samples of all reachable voxels in layer `k`.
+    pub fn layer(&self, k: usize) -> impl Iterator<Item = (Vec3, VoxelSample)> + '_ {
+        (0..self.dims[1]).flat_map(move |j| {
+            (0..self.dims[0]).filter_map(move |i| {
+                let s = self.cell(i, j, k);
+                s.reachable().then(|| (self.center(i, j, k), s))
+            })
+        })
+    }
+
+    /// Share of voxels (above the floor, inside the grid) that are reachable.
+    pub fn reachable_fraction(&self) -> f64 {
+        let n = self.cells.iter().filter(|c| c.reachable()).count();
+        n as f64 / self.cells.len().max(1) as f64
+    }
+
+    /// Plain-text voxel file: a small header followed by one
+    /// `i j k solutions manipulability` line per reachable voxel.
+    pub fn write_voxels<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "# manipulator3d workspace voxels v1")?;
+        writeln!(w, "origin {} {} {}", self.origin.x, self.origin.y, self.origin.z)?;
+        writeln!(w, "resolution {}", self.resolution)?;
+        writeln!(w, "dims {} {} {}", self.dims[0], self.dims[1], self.dims[2])?;
+        writeln!(w, "max_manipulability {}", self.max_manipulability)?;
+
+        for k in 0..self.dims[2] {
+            for j in 0..self.dims[1] {
+                for i in 0..self.dims[0] {
+                    let c = self.cell(i, j, k);
+                    if c.reachable() {
+                        writeln!(w, "{} {} {} {} {}", i, j, k, c.solutions, c.manipulability)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save_voxels(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.write_voxels(io::BufWriter::new(file))
+    }
+
+    fn index(&self, i: usize, j: usize, k: usize) -> usize {
+        (k * self.dims[1] + j) * self.dims[0] + i
+    }
+}
+
+/// Sweep a regular grid over the joint ranges and keep the TCP positions whose
+/// configuration keeps every link point above the floor.
+pub fn sample_joint_space(arm: &RobotArm, params: &WorkspaceParams) -> Vec<WorkspacePoint> {
+    let n = params.joint_steps.max(2);
+    let limits = arm.joint_limits();
+    let ranges = [range(&limits[0]), range(&limits[1]), range(&limits[2])];
+    let at = |r: (f64, f64), i: usize| r.0 + (r.1 - r.0) * i as f64 / (n - 1) as f64;
+
+    let mut out = Vec::with_capacity(n * n * n);
+    for a in 0..n {
+        for b in 0..n {
+            for c in 0..n {
+                let q = JointAngles {
+                    q0_yaw: at(ranges[0], a),
+                    q1_pitch: at(ranges[1], b),
+                    q2_pitch: at(ranges[2], c),
+                };
+                let fk = arm.forward_kinematics(q);
+                if !clears_floor(&fk, params.floor_z) {
+                    continue;
+                }
+                out.push(WorkspacePoint {
+                    position: fk.tcp,
+                    q,
+                    manipulability: arm.jacobian_metrics(q).manipulability,
+                });
+            }
+        }
+    }
+    out
+}
+
+fn sample_point(arm: &RobotArm, p: Vec3, floor_z: f64) -> VoxelSample {
+    let mut out = VoxelSample::default();
+    if p.z < floor_z {
+        return out;
+    }
+
+    for s in arm.solve_ik_all(p, JointAngles::default()) {
+        if !s.within_limits || !clears_floor(&arm.forward_kinematics(s.q), floor_z) {
+            continue;
+        }
+        out.solutions += 1;
+        out.manipulability = out.manipulability.max(arm.jacobian_metrics(s.q).manipulability);
+    }
+    out
+}
+
+// Height is linear along a straight link, so each link's lowest point is one of its
+// ends: checking the joints and the TCP covers the whole chain
+fn clears_floor(fk: &FKResult, floor_z: f64) -> bool {
+    [fk.joint1, fk.joint2, fk.ee, fk.tcp].iter().all(|p| p.z >= floor_z - 1e-4)
+}
+
+// Revolute joints repeat every turn, so wider (or unlimited) ranges collapse to one
+fn range(lim: &JointLimits) -> (f64, f64) {
+    let pi = std::f64::consts::PI;
+    if lim.min_rad.is_finite() && lim.max_rad.is_finite() && lim.max_rad - lim.min_rad <= 2.0 * pi {
+        (lim.min_rad, lim.max_rad)
+    } else {
+        (-pi, pi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robot::{LinkParams, Transform};
+
+    /// 3.0 m and 2.6 m links without joint limits or TCP offset: reach is exactly [0.4, 5.6] m.
+    fn arm() -> RobotArm {
+        RobotArm::new(
+            LinkParams { length_m: 3.0, ..Default::default() },
+            LinkParams { length_m: 2.6, ..Default::default() },
+        )
+    }
+
+    fn params(floor_z: f64) -> WorkspaceParams {
+        WorkspaceParams {
+            resolution_m: 0.5,
+            floor_z,
+            joint_steps: 12,
+        }
+    }
+
+    #[test]
+    fn reachable_voxels_end_at_max_reach() {
+        let arm = arm();
+        let map = WorkspaceMap::build(&arm, &params(0.0));
+        let [nx, ny, nz] = map.dims();
+
+        let mut outermost = 0.0_f64;
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    let d = arm.distance_from_base(map.center(i, j, k));
+                    if map.cell(i, j, k).reachable() {
+                        assert!(d <= arm.max_reach(), "voxel ({i}, {j}, {k}) at {d:.3} m");
+                        outermost = outermost.max(d);
+                    }
+                }
+            }
+        }
+        assert!(outermost > arm.max_reach() - map.resolution(), "outermost reachable voxel at {outermost:.3} m");
+    }
+
+    #[test]
+    fn nothing_below_the_floor_is_reachable() {
+        // On a 3 m pedestal the reach sphere extends well below a 2 m floor
+        let arm = arm().with_base(Transform::from_translation(0.0, 0.0, 3.0));
+        let below = Vec3::new(3.0, 0.0, 1.5);
+        assert!(sample_point(&arm, below, 1.0).reachable());
+        assert!(!sample_point(&arm, below, 2.0).reachable());
+
+        let params = params(2.0);
+        let map = WorkspaceMap::build(&arm, &params);
+        assert!(map.origin().z >= params.floor_z);
+        assert!(map.reachable_fraction() > 0.0);
+
+        let points = sample_joint_space(&arm, &params);
+        assert!(!points.is_empty() && points.len() < params.joint_steps.pow(3));
+        for p in points {
+            let fk = arm.forward_kinematics(p.q);
+            assert!([fk.joint2, fk.ee, fk.tcp].iter().all(|x| x.z >= params.floor_z - 1e-4), "{:?}", p.q);
+        }
+    }
+
+    #[test]
+    fn voxel_file_lists_every_reachable_voxel() {
+        let map = WorkspaceMap::build(&arm(), &params(0.0));
+        let mut out = Vec::new();
+        map.write_voxels(&mut out).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("# manipulator3d workspace voxels v1"));
+        let header: Vec<&str> = lines.by_ref().take(4).collect();
+        let [nx, ny, nz] = map.dims();
+        assert_eq!(header[2], format!("dims {nx} {ny} {nz}"));
+
+        let mut reachable = 0;
+        for line in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [i, j, k, solutions]: [usize; 4] = std::array::from_fn(|n| fields[n].parse().unwrap());
+            assert_eq!(map.cell(i, j, k).solutions as usize, solutions);
+            assert!(map.cell(i, j, k).reachable());
+            reachable += 1;
+        }
+        let expected = (map.reachable_fraction() * (nx * ny * nz) as f64).round() as usize;
+        assert_eq!(reachable, expected);
+    }
+}
@@ -0,0 +1,12 @@
+use raylib::prelude::Vector3;
+
+use manipulator3d::robot::Vec3;
+
+/// Kinematics core (f64) -> raylib (f32), for drawing.
+pub fn to_vector3(v: Vec3) -> Vector3 {
+    Vector3 {
+        x: v.x as f32,
+        y: v.y as f32,
+        z: v.z as f32,
+    }
+}
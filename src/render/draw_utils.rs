@@ -35,18 +35,21 @@ where
     draw_text_ex_at(d, font, text, x, y, font_size, color);
 }
 
-pub fn draw_robot_base_pedestal<D: RaylibDraw3D>(d: &mut D, origin: Vector3) {
-    let a = Vector3 { x: origin.x, y: origin.y, z: -0.25 };
-    let b = Vector3 { x: origin.x, y: origin.y, z:  0.00 };
-    d.draw_cylinder_ex(a, b, 0.55, 0.55, 24, Color::new(70, 70, 75, 255));
+/// Pedestal below `origin`, extruded along the base mounting axis `up` (unit length).
+pub fn draw_robot_base_pedestal<D: RaylibDraw3D>(d: &mut D, origin: Vector3, up: Vector3) {
+    let at = |h: f32| Vector3 {
+        x: origin.x + up.x * h,
+        y: origin.y + up.y * h,
+        z: origin.z + up.z * h,
+    };
+
+    d.draw_cylinder_ex(at(-0.25), at(0.00), 0.55, 0.55, 24, Color::new(70, 70, 75, 255));
 
-    let c = Vector3 { x: origin.x, y: origin.y, z: 0.00 };
-    let d2 = Vector3 { x: origin.x, y: origin.y, z: 0.35 };
-    d.draw_cylinder_ex(c, d2, 0.38, 0.34, 24, Color::new(95, 95, 100, 255));
+    d.draw_cylinder_ex(at(0.00), at(0.35), 0.38, 0.34, 24, Color::new(95, 95, 100, 255));
 
     d.draw_cylinder_ex(
-        Vector3 { x: origin.x, y: origin.y, z: 0.00 },
-        Vector3 { x: origin.x, y: origin.y, z: 0.06 },
+        at(0.00),
+        at(0.06),
         0.48,
         0.48,
         24,
@@ -73,20 +76,41 @@ pub fn draw_tapered_link<D: RaylibDraw3D>(d: &mut D, a: Vector3, b: Vector3, r_a
     d.draw_sphere(b, r_b * 0.95, Color::new(140, 140, 145, 255));
 }
 
-pub fn draw_suction_tool<D: RaylibDraw3D>(d: &mut D, ee: Vector3, approach_dir: Vector3) {
+pub fn draw_suction_tool<D: RaylibDraw3D>(d: &mut D, ee: Vector3, tcp: Vector3, approach_dir: Vector3) {
+    // Cup face ends at the TCP, facing along the tool approach axis
     let tip = Vector3 {
-        x: ee.x + approach_dir.x * 0.28,
-        y: ee.y + approach_dir.y * 0.28,
-        z: ee.z + approach_dir.z * 0.28,
+        x: tcp.x - approach_dir.x * 0.06,
+        y: tcp.y - approach_dir.y * 0.06,
+        z: tcp.z - approach_dir.z * 0.06,
     };
     d.draw_cylinder_ex(ee, tip, 0.06, 0.05, 18, Color::new(40, 40, 45, 255));
 
-    let cup_a = tip;
-    let cup_b = Vector3 {
-        x: tip.x + approach_dir.x * 0.06,
-        y: tip.y + approach_dir.y * 0.06,
-        z: tip.z + approach_dir.z * 0.06,
-    };
-    d.draw_cylinder_ex(cup_a, cup_b, 0.11, 0.11, 24, Color::new(25, 25, 28, 255));
+    d.draw_cylinder_ex(tip, tcp, 0.11, 0.11, 24, Color::new(25, 25, 28, 255));
     d.draw_sphere(tip, 0.035, Color::new(80, 80, 85, 255));
 }
+
+/// Heatmap color for a value in [0, 1]: red (poor) -> yellow -> green (good).
+pub fn heatmap_color(t: f32, alpha: u8) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let (r, g) = if t < 0.5 {
+        (1.0, t * 2.0)
+    } else {
+        (2.0 - t * 2.0, 1.0)
+    };
+    Color::new((r * 230.0) as u8, (g * 210.0) as u8, 40, alpha)
+}
+
+/// Small cubes at each point, colored by its value in [0, 1].
+pub fn draw_point_cloud<D: RaylibDraw3D>(d: &mut D, points: &[(Vector3, f32)], size: f32) {
+    for &(p, v) in points {
+        d.draw_cube(p, size, size, size, heatmap_color(v, 200));
+    }
+}
+
+/// Flat translucent tiles of edge `cell` centered on each point, colored by value.
+pub fn draw_heatmap_slice<D: RaylibDraw3D>(d: &mut D, cells: &[(Vector3, f32)], cell: f32) {
+    let tile = cell * 0.92;
+    for &(p, v) in cells {
+        d.draw_cube(p, tile, tile, 0.02, heatmap_color(v, 150));
+    }
+}
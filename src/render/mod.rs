@@ -1,3 +1,5 @@
+pub mod convert;
 pub mod draw_utils;
 
+pub use convert::*;
 pub use draw_utils::*;
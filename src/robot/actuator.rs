@@ -0,0 +1,70 @@
+/// Motor and gearbox driving one joint. Rotor inertia and torque limit are motor-side
+/// datasheet values; friction and backlash are measured at the gearbox output.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ActuatorParams {
+    /// Motor turns per joint turn
+    pub gear_ratio: f64,
+    /// Rotor inertia about the motor shaft (kg m^2)
+    pub rotor_inertia: f64,
+    /// Viscous friction at the joint (N m s/rad)
+    pub viscous_nm_s_per_rad: f64,
+    /// Kinetic (Coulomb) friction at the joint while it slides (N m)
+    pub coulomb_nm: f64,
+    /// Breakaway friction that holds a resting joint; at least `coulomb_nm` (N m)
+    pub static_nm: f64,
+    /// Total free play of the gear train at the joint (rad)
+    pub backlash_rad: f64,
+    /// Peak motor torque (N m); infinite for an ideal drive
+    pub max_motor_torque_nm: f64,
+}
+
+impl ActuatorParams {
+    /// Direct drive without inertia, friction, play or torque limit.
+    pub const IDEAL: ActuatorParams = ActuatorParams {
+        gear_ratio: 1.0,
+        rotor_inertia: 0.0,
+        viscous_nm_s_per_rad: 0.0,
+        coulomb_nm: 0.0,
+        static_nm: 0.0,
+        backlash_rad: 0.0,
+        max_motor_torque_nm: f64::INFINITY,
+    };
+
+    /// Rotor inertia as seen at the joint, N² J (kg m^2).
+    pub fn reflected_inertia(&self) -> f64 {
+        self.gear_ratio * self.gear_ratio * self.rotor_inertia
+    }
+
+    /// Largest torque the drive can put on the joint (N m).
+    pub fn max_joint_torque(&self) -> f64 {
+        self.gear_ratio.abs() * self.max_motor_torque_nm
+    }
+
+    /// `tau` clipped to what the motor can deliver.
+    pub fn saturate(&self, tau: f64) -> f64 {
+        let max = self.max_joint_torque();
+        tau.clamp(-max, max)
+    }
+
+    /// Friction torque at joint rate `qd` while sliding; `direction` (±1, or 0 at rest)
+    /// picks the sign of the Coulomb part.
+    pub fn friction(&self, qd: f64, direction: f64) -> f64 {
+        self.viscous_nm_s_per_rad * qd + self.coulomb_nm * direction
+    }
+
+    /// Torque a resting joint can resist before it breaks away (N m).
+    pub fn breakaway_nm(&self) -> f64 {
+        self.static_nm.max(self.coulomb_nm)
+    }
+
+    /// Whether the gear train has play the motor can move through on its own.
+    pub fn has_backlash(&self) -> bool {
+        self.backlash_rad > 0.0 && self.reflected_inertia() > 0.0
+    }
+}
+
+impl Default for ActuatorParams {
+    fn default() -> Self {
+        Self::IDEAL
+    }
+}
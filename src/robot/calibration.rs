@@ -0,0 +1,387 @@
+use std::fmt;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+use super::math::Vec3;
+use super::numeric_ik::solve_linear;
+use super::robot_arm::{JointAngles, RobotArm};
+use super::transform::Transform;
+
+/// Names of the fitted parameters, in the order of `KinematicParams::to_array`.
+pub const PARAM_NAMES: [&str; 8] = [
+    "l1_m",
+    "l2_m",
+    "q0_offset_rad",
+    "q1_offset_rad",
+    "q2_offset_rad",
+    "base_dx_m",
+    "base_dy_m",
+    "base_dz_m",
+];
+
+/// Central-difference step of the numeric Jacobian (m or rad).
+const JACOBIAN_STEP: f64 = 1e-6;
+
+/// Scaled normal-matrix inverse diagonal above which a parameter counts as unidentifiable.
+const MAX_VARIANCE_GAIN: f64 = 1e10;
+
+/// One calibration sample: commanded joint angles (hardware convention) and the TCP
+/// position measured for them (world frame, m), e.g. by a laser tracker.
+#[derive(Copy, Clone, Debug)]
+pub struct Measurement {
+    pub q: JointAngles,
+    pub position: Vec3,
+}
+
+/// Kinematic parameters identified by `calibrate`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct KinematicParams {
+    pub l1_m: f64,
+    pub l2_m: f64,
+    /// Correction of each joint's `JointConvention::zero_offset_rad` (yaw, shoulder, elbow)
+    pub joint_offsets_rad: [f64; 3],
+    /// Shift of the base origin in the world frame
+    pub base_offset: Vec3,
+}
+
+impl KinematicParams {
+    /// Parameters of `arm` as modelled: its link lengths and no corrections.
+    pub fn nominal(arm: &RobotArm) -> Self {
+        Self {
+            l1_m: arm.l1(),
+            l2_m: arm.l2(),
+            ..Self::default()
+        }
+    }
+
+    pub fn to_array(&self) -> [f64; 8] {
+        let [o0, o1, o2] = self.joint_offsets_rad;
+        let b = self.base_offset;
+        [self.l1_m, self.l2_m, o0, o1, o2, b.x, b.y, b.z]
+    }
+
+    pub fn from_array(p: [f64; 8]) -> Self {
+        Self {
+            l1_m: p[0],
+            l2_m: p[1],
+            joint_offsets_rad: [p[2], p[3], p[4]],
+            base_offset: Vec3::new(p[5], p[6], p[7]),
+        }
+    }
+
+    /// `arm` with the fitted link lengths, base shift and joint zero offsets (added to
+    /// the arm's joint conventions). Link inertias follow the new lengths.
+    pub fn apply(&self, arm: &RobotArm) -> RobotArm {
+        let mut link1 = *arm.link1();
+        let mut link2 = *arm.link2();
+        link1.length_m = self.l1_m;
+        link2.length_m = self.l2_m;
+        let base = Transform {
+            translation: arm.base().translation + self.base_offset,
+            ..*arm.base()
+        };
+
+        let mut conventions = arm.joint_conventions();
+        for (c, offset) in conventions.iter_mut().zip(self.joint_offsets_rad) {
+            c.zero_offset_rad += offset;
+        }
+
+        RobotArm::new(link1, link2)
+            .with_base(base)
+            .with_tcp(*arm.tcp())
+            .with_yaw_limits(arm.joint_limits()[0])
+            .with_joint_conventions(conventions)
+            .with_actuators(*arm.actuators())
+    }
+
+    /// TCP position predicted for hardware angles `q` on `arm` adjusted by these parameters.
+    pub fn predict(&self, arm: &RobotArm, q: JointAngles) -> Vec3 {
+        let model = self.apply(arm);
+        model.forward_kinematics(model.from_hardware(q)).tcp
+    }
+}
+
+/// Settings for the Levenberg–Marquardt fit.
+#[derive(Copy, Clone, Debug)]
+pub struct CalibrationParams {
+    pub max_iterations: usize,
+    /// Stop once the parameter update norm drops below this (m or rad)
+    pub tolerance: f64,
+    /// Initial damping, relative to the diagonal of the normal matrix
+    pub damping: f64,
+}
+
+impl Default for CalibrationParams {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            tolerance: 1e-10,
+            damping: 1e-3,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Calibration {
+    pub params: KinematicParams,
+    /// One-sigma uncertainty of each parameter, from the residual variance
+    pub std_dev: KinematicParams,
+    /// Measured minus predicted TCP position, one per measurement (m)
+    pub residuals: Vec<Vec3>,
+    pub rms_m: f64,
+    pub max_m: f64,
+    /// RMS residual of the uncalibrated model, for comparison
+    pub initial_rms_m: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    Io { path: PathBuf, source: io::Error },
+    /// Bad CSV row, 1-based line number
+    Parse { line: usize, message: String },
+    TooFewMeasurements { got: usize, needed: usize },
+    /// The measurements do not pin down every parameter (e.g. all at one pose)
+    Unidentifiable(String),
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationError::Io { path, source } => write!(f, "cannot read {}: {source}", path.display()),
+            CalibrationError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            CalibrationError::TooFewMeasurements { got, needed } => {
+                write!(f, "{got} measurements, calibration needs at least {needed}")
+            }
+            CalibrationError::Unidentifiable(e) => write!(f, "parameters not identifiable: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+/// Least-squares fit of link lengths, joint zero offsets and base shift to measured
+/// TCP positions, starting from the nominal model of `arm`. The TCP transform and
+/// base rotation are taken as known.
+pub fn calibrate(
+    arm: &RobotArm,
+    data: &[Measurement],
+    params: &CalibrationParams,
+) -> Result<Calibration, CalibrationError> {
+    // Strictly more equations than unknowns, so the residual variance is defined
+    let needed = PARAM_NAMES.len() / 3 + 1;
+    if data.len() < needed {
+        return Err(CalibrationError::TooFewMeasurements { got: data.len(), needed });
+    }
+
+    let mut p = KinematicParams::nominal(arm).to_array();
+    let mut r = residuals(arm, data, &p);
+    let mut cost = sum_squares(&r);
+    let initial_rms_m = (cost / data.len() as f64).sqrt();
+
+    let mut lambda = params.damping.max(1e-12);
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < params.max_iterations {
+        iterations += 1;
+        let jac = jacobian(arm, data, &p);
+        let (a, g) = normal_equations(&jac, &r);
+
+        let mut damped = a.clone();
+        for (i, row) in damped.iter_mut().enumerate() {
+            row[i] += lambda * a[i][i].max(1e-12);
+        }
+        let Some(step) = solve_linear(damped, g) else {
+            lambda *= 10.0;
+            continue;
+        };
+
+        let mut trial = p;
+        for (t, s) in trial.iter_mut().zip(&step) {
+            *t += s;
+        }
+        let trial_r = residuals(arm, data, &trial);
+        let trial_cost = sum_squares(&trial_r);
+
+        if trial_cost <= cost {
+            p = trial;
+            r = trial_r;
+            cost = trial_cost;
+            lambda = (lambda * 0.1).max(1e-12);
+            if step.iter().map(|s| s * s).sum::<f64>().sqrt() < params.tolerance {
+                converged = true;
+                break;
+            }
+        } else {
+            lambda *= 10.0;
+            if lambda > 1e12 {
+                // No downhill step left: at a minimum up to numerical noise
+                converged = true;
+                break;
+            }
+        }
+    }
+
+    let jac = jacobian(arm, data, &p);
+    let (a, _) = normal_equations(&jac, &r);
+    let variance = cost / (r.len() - p.len()) as f64;
+    let std_dev = covariance_diagonal(&a)?.map(|c| (variance * c).sqrt());
+
+    let residuals: Vec<Vec3> = r.chunks(3).map(|c| Vec3::new(c[0], c[1], c[2])).collect();
+    let max_m = residuals.iter().map(|e| e.length()).fold(0.0, f64::max);
+
+    Ok(Calibration {
+        params: KinematicParams::from_array(p),
+        std_dev: KinematicParams::from_array(std_dev),
+        residuals,
+        rms_m: (cost / data.len() as f64).sqrt(),
+        max_m,
+        initial_rms_m,
+        iterations,
+        converged,
+    })
+}
+
+/// Parse measurements from CSV rows `q0,q1,q2,x,y,z` (rad, m). Blank lines and
+/// lines starting with `#` are skipped, as is a header row before the first sample.
+pub fn read_measurements<R: BufRead>(r: R) -> Result<Vec<Measurement>, CalibrationError> {
+    let mut out = Vec::new();
+    let mut header_allowed = true;
+
+    for (i, line) in r.lines().enumerate() {
+        let line = line.map_err(|e| CalibrationError::Parse {
+            line: i + 1,
+            message: e.to_string(),
+        })?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let values: Result<Vec<f64>, _> = fields.iter().map(|f| f.parse::<f64>()).collect();
+        let values = match values {
+            Ok(v) => v,
+            Err(_) if header_allowed => {
+                header_allowed = false;
+                continue;
+            }
+            Err(e) => {
+                return Err(CalibrationError::Parse {
+                    line: i + 1,
+                    message: format!("{e} in '{line}'"),
+                })
+            }
+        };
+        header_allowed = false;
+
+        let [q0, q1, q2, x, y, z] = values[..] else {
+            return Err(CalibrationError::Parse {
+                line: i + 1,
+                message: format!("expected 6 columns (q0,q1,q2,x,y,z), found {}", values.len()),
+            });
+        };
+        out.push(Measurement {
+            q: JointAngles {
+                q0_yaw: q0,
+                q1_pitch: q1,
+                q2_pitch: q2,
+            },
+            position: Vec3::new(x, y, z),
+        });
+    }
+    Ok(out)
+}
+
+pub fn load_measurements(path: impl AsRef<Path>) -> Result<Vec<Measurement>, CalibrationError> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path).map_err(|source| CalibrationError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    read_measurements(io::BufReader::new(file))
+}
+
+/// Stacked `measured - predicted` TCP positions.
+fn residuals(arm: &RobotArm, data: &[Measurement], p: &[f64; 8]) -> Vec<f64> {
+    let params = KinematicParams::from_array(*p);
+    let model = params.apply(arm);
+    data.iter()
+        .flat_map(|m| {
+            let e = m.position - model.forward_kinematics(model.from_hardware(m.q)).tcp;
+            e.to_array()
+        })
+        .collect()
+}
+
+/// Jacobian of the predicted positions w.r.t. the parameters, one row per residual.
+fn jacobian(arm: &RobotArm, data: &[Measurement], p: &[f64; 8]) -> Vec<[f64; 8]> {
+    let mut jac = vec![[0.0; 8]; data.len() * 3];
+    for k in 0..p.len() {
+        let mut plus = *p;
+        let mut minus = *p;
+        plus[k] += JACOBIAN_STEP;
+        minus[k] -= JACOBIAN_STEP;
+        // Residuals are measured - predicted, so their difference flips sign
+        let rp = residuals(arm, data, &plus);
+        let rm = residuals(arm, data, &minus);
+        for (row, (a, b)) in jac.iter_mut().zip(rp.iter().zip(&rm)) {
+            row[k] = (b - a) / (2.0 * JACOBIAN_STEP);
+        }
+    }
+    jac
+}
+
+/// `(JᵀJ, Jᵀr)`.
+fn normal_equations(jac: &[[f64; 8]], r: &[f64]) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let n = PARAM_NAMES.len();
+    let mut a = vec![vec![0.0; n]; n];
+    let mut g = vec![0.0; n];
+    for (row, e) in jac.iter().zip(r) {
+        for i in 0..n {
+            g[i] += row[i] * e;
+            for j in 0..n {
+                a[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    (a, g)
+}
+
+/// Diagonal of `(JᵀJ)⁻¹`, solved on the unit-diagonal scaled matrix so the
+/// identifiability check does not depend on parameter units.
+fn covariance_diagonal(a: &[Vec<f64>]) -> Result<[f64; 8], CalibrationError> {
+    let n = PARAM_NAMES.len();
+    let scale: Vec<f64> = (0..n).map(|i| a[i][i].sqrt()).collect();
+    if let Some(i) = (0..n).find(|&i| scale[i] < 1e-12) {
+        return Err(CalibrationError::Unidentifiable(format!(
+            "{} has no effect on the measured positions",
+            PARAM_NAMES[i]
+        )));
+    }
+    let scaled: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| a[i][j] / (scale[i] * scale[j])).collect())
+        .collect();
+
+    let mut diag = [0.0; 8];
+    for i in 0..n {
+        let mut unit = vec![0.0; n];
+        unit[i] = 1.0;
+        let col = solve_linear(scaled.clone(), unit)
+            .ok_or_else(|| CalibrationError::Unidentifiable("normal matrix is singular".into()))?;
+        if col[i].is_nan() || col[i].abs() >= MAX_VARIANCE_GAIN {
+            return Err(CalibrationError::Unidentifiable(format!(
+                "{} is correlated with other parameters; measure more varied poses",
+                PARAM_NAMES[i]
+            )));
+        }
+        diag[i] = col[i] / (scale[i] * scale[i]);
+    }
+    Ok(diag)
+}
+
+fn sum_squares(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum()
+}
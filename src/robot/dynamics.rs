@@ -0,0 +1,262 @@
+use super::math::{Mat3, Vec3};
+use super::numeric_ik::solve_linear;
+use super::robot_arm::{JointAngles, LinkParams, RobotArm};
+use super::transform::Transform;
+
+/// Gravity in the world frame (z up, m/s^2).
+pub const GRAVITY: Vec3 = Vec3::new(0.0, 0.0, -9.81);
+
+/// Load rigidly held at the TCP, e.g. a picked part. Set with `RobotArm::set_payload`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Payload {
+    pub mass_kg: f64,
+    /// COM in the tool (TCP) frame
+    pub com: Vec3,
+    /// Inertia about the COM, tool frame axes (kg m^2)
+    pub inertia: Mat3,
+}
+
+impl Payload {
+    /// Solid sphere held at its surface by the TCP: the centre sits one radius out along
+    /// the tool x axis.
+    pub fn solid_sphere(mass_kg: f64, radius_m: f64) -> Self {
+        let i = 0.4 * mass_kg * radius_m * radius_m;
+        Self {
+            mass_kg,
+            com: Vec3::new(radius_m, 0.0, 0.0),
+            inertia: Mat3([[i, 0.0, 0.0], [0.0, i, 0.0], [0.0, 0.0, i]]),
+        }
+    }
+}
+
+/// Joint torques split by origin; `total` is their sum (N m).
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TorqueBreakdown {
+    /// M(q) q̈
+    pub inertial: [f64; 3],
+    /// Coriolis and centrifugal terms C(q, q̇) q̇
+    pub velocity: [f64; 3],
+    /// Holding torque against gravity g(q)
+    pub gravity: [f64; 3],
+    pub total: [f64; 3],
+}
+
+/// Peak and RMS joint torques over a motion, e.g. one pick-and-place cycle.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TorqueStats {
+    /// Largest absolute torque per joint (N m)
+    pub peak: [f64; 3],
+    sum_sq: [f64; 3],
+    duration_s: f64,
+}
+
+impl TorqueStats {
+    /// Add a torque sample held for `dt` seconds.
+    pub fn add(&mut self, tau: [f64; 3], dt: f64) {
+        for ((peak, sum_sq), t) in self.peak.iter_mut().zip(&mut self.sum_sq).zip(tau) {
+            *peak = peak.max(t.abs());
+            *sum_sq += t * t * dt;
+        }
+        self.duration_s += dt;
+    }
+
+    /// Time-weighted RMS torque per joint (the continuous rating a motor needs).
+    pub fn rms(&self) -> [f64; 3] {
+        if self.duration_s <= 0.0 {
+            return [0.0; 3];
+        }
+        self.sum_sq.map(|s| (s / self.duration_s).sqrt())
+    }
+
+    pub fn duration_s(&self) -> f64 {
+        self.duration_s
+    }
+}
+
+impl RobotArm {
+    /// Joint torques that produce accelerations `qdd` at state (`q`, `qd`) under `GRAVITY`,
+    /// by recursive Newton–Euler over the link masses and inertias in `LinkParams`, plus
+    /// the reflected rotor inertia of each drive.
+    pub fn inverse_dynamics(&self, q: JointAngles, qd: [f64; 3], qdd: [f64; 3]) -> [f64; 3] {
+        self.rnea(q, qd, qdd, GRAVITY)
+    }
+
+    /// Torques that hold the arm still at `q`.
+    pub fn gravity_torques(&self, q: JointAngles) -> [f64; 3] {
+        self.rnea(q, [0.0; 3], [0.0; 3], GRAVITY)
+    }
+
+    pub fn torque_breakdown(&self, q: JointAngles, qd: [f64; 3], qdd: [f64; 3]) -> TorqueBreakdown {
+        let inertial = self.rnea(q, [0.0; 3], qdd, Vec3::default());
+        let velocity = self.rnea(q, qd, [0.0; 3], Vec3::default());
+        let gravity = self.gravity_torques(q);
+        TorqueBreakdown {
+            inertial,
+            velocity,
+            gravity,
+            total: std::array::from_fn(|i| inertial[i] + velocity[i] + gravity[i]),
+        }
+    }
+
+    /// Joint-space mass matrix M(q), column j = torques for a unit acceleration of joint j.
+    pub fn mass_matrix(&self, q: JointAngles) -> Mat3 {
+        let mut m = Mat3::ZERO;
+        for j in 0..3 {
+            let mut qdd = [0.0; 3];
+            qdd[j] = 1.0;
+            let col = self.rnea(q, [0.0; 3], qdd, Vec3::default());
+            for (i, c) in col.into_iter().enumerate() {
+                m[i][j] = c;
+            }
+        }
+        m
+    }
+
+    /// Joint accelerations produced by torques `tau` at state (`q`, `qd`):
+    /// M(q)⁻¹ (τ − C(q, q̇) q̇ − g(q)). Zero if M(q) is singular (massless links).
+    pub fn forward_dynamics(&self, q: JointAngles, qd: [f64; 3], tau: [f64; 3]) -> [f64; 3] {
+        let bias = self.rnea(q, qd, [0.0; 3], GRAVITY);
+        let m = self.mass_matrix(q);
+        let a = m.0.iter().map(|row| row.to_vec()).collect();
+        let b = (0..3).map(|i| tau[i] - bias[i]).collect();
+        solve_linear(a, b).map_or([0.0; 3], |x| [x[0], x[1], x[2]])
+    }
+
+    /// Newton–Euler in world coordinates. Joint i turns about z of FK frame i; link 1
+    /// rides on frame 2 and link 2 on frame 3, each reaching back along -x to its joint.
+    /// The yaw body between base and shoulder is massless; the payload moves with link 2.
+    fn rnea(&self, q: JointAngles, qd: [f64; 3], qdd: [f64; 3], gravity: Vec3) -> [f64; 3] {
+        let fk = self.forward_kinematics(q);
+        let frames = fk.frames;
+        let mut carried = [
+            MassProps::NONE,
+            MassProps::link(&frames[2], self.link1()),
+            MassProps::link(&frames[3], self.link2()),
+        ];
+        if let Some(payload) = self.payload() {
+            carried[2] = carried[2].combine(MassProps::payload(&fk.tool, payload));
+        }
+
+        // Outward pass: body velocities and the accelerations of joint points / COMs
+        let mut omega = Vec3::default();
+        let mut alpha = Vec3::default();
+        // Gravity enters as an upward acceleration of the base
+        let mut acc = -gravity;
+        let mut bodies = Vec::with_capacity(3);
+        for i in 0..3 {
+            let z = frames[i].axis(2);
+            let p = frames[i].translation;
+            let alpha_next = alpha + z * qdd[i] + omega.cross(z * qd[i]);
+            let omega_next = omega + z * qd[i];
+            let point_acc = |r: Vec3| acc + alpha_next.cross(r) + omega_next.cross(omega_next.cross(r));
+
+            let props = carried[i];
+            bodies.push(Body {
+                props,
+                com_acc: point_acc(props.com - p),
+                omega: omega_next,
+                alpha: alpha_next,
+            });
+
+            if i + 1 < 3 {
+                acc = point_acc(frames[i + 1].translation - p);
+            }
+            omega = omega_next;
+            alpha = alpha_next;
+        }
+
+        // Inward pass: force and moment each joint transmits to the bodies beyond it
+        let mut force = Vec3::default();
+        let mut moment = Vec3::default();
+        let mut next_joint = frames[3].translation;
+        let mut tau = [0.0; 3];
+        for i in (0..3).rev() {
+            let b = &bodies[i];
+            let m = &b.props;
+            let p = frames[i].translation;
+            let f_body = b.com_acc * m.mass;
+            let iw = m.inertia * b.omega;
+            let n_body = m.inertia * b.alpha + b.omega.cross(iw);
+
+            moment = n_body + (m.com - p).cross(f_body) + moment + (next_joint - p).cross(force);
+            force = f_body + force;
+            next_joint = p;
+            tau[i] = frames[i].axis(2).dot(moment);
+        }
+
+        // Rotors spin N times faster than their joint; gyroscopic terms are negligible
+        for ((t, a), qdd) in tau.iter_mut().zip(self.actuators()).zip(qdd) {
+            *t += a.reflected_inertia() * qdd;
+        }
+        tau
+    }
+}
+
+/// Mass, COM and inertia about the COM (world axes) of what one joint carries directly.
+#[derive(Copy, Clone)]
+struct MassProps {
+    mass: f64,
+    com: Vec3,
+    inertia: Mat3,
+}
+
+impl MassProps {
+    const NONE: MassProps = MassProps {
+        mass: 0.0,
+        com: Vec3::new(0.0, 0.0, 0.0),
+        inertia: Mat3::ZERO,
+    };
+
+    /// Link whose axis is x of `frame`, with `frame` at its far end.
+    fn link(frame: &Transform, link: &LinkParams) -> Self {
+        let [ixx, iyy, izz] = link.principal_inertia();
+        let diag = Mat3([[ixx, 0.0, 0.0], [0.0, iyy, 0.0], [0.0, 0.0, izz]]);
+        Self {
+            mass: link.mass_kg,
+            com: frame.transform_point(Vec3::new(link.com_offset_m() - link.length_m, 0.0, 0.0)),
+            inertia: rotate_inertia(frame, diag),
+        }
+    }
+
+    fn payload(tool: &Transform, payload: &Payload) -> Self {
+        Self {
+            mass: payload.mass_kg,
+            com: tool.transform_point(payload.com),
+            inertia: rotate_inertia(tool, payload.inertia),
+        }
+    }
+
+    /// Rigid union of two bodies (parallel-axis theorem about the joint COM).
+    fn combine(self, other: MassProps) -> MassProps {
+        let mass = self.mass + other.mass;
+        if mass <= 0.0 {
+            return self;
+        }
+        let com = (self.com * self.mass + other.com * other.mass) / mass;
+        let mut inertia = Mat3::ZERO;
+        for b in [self, other] {
+            let d = b.com - com;
+            let dd = d.to_array();
+            for r in 0..3 {
+                for c in 0..3 {
+                    let shift = if r == c { d.dot(d) } else { 0.0 } - dd[r] * dd[c];
+                    inertia[r][c] += b.inertia[r][c] + b.mass * shift;
+                }
+            }
+        }
+        MassProps { mass, com, inertia }
+    }
+}
+
+struct Body {
+    props: MassProps,
+    com_acc: Vec3,
+    omega: Vec3,
+    alpha: Vec3,
+}
+
+/// R I Rᵀ: inertia given in `frame` axes expressed in world axes.
+fn rotate_inertia(frame: &Transform, inertia: Mat3) -> Mat3 {
+    let r = frame.rotation;
+    r * inertia * r.transpose()
+}
@@ -0,0 +1,230 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use super::math::Mat3;
+use super::robot_arm::{JointLimits, LinkParams, RobotArm, JOINT_NAMES};
+use super::transform::Transform;
+
+/// Stand-in for limits the arm does not model (URDF requires effort and velocity).
+const UNLIMITED: f64 = 1e6;
+
+/// Link 2 frame to DH flange frame: x stays along the link, y becomes the yaw axis.
+fn flange_rotation() -> Transform {
+    Transform::rot_x(std::f64::consts::FRAC_PI_2)
+}
+
+impl RobotArm {
+    /// URDF of the arm: base_link -> upper_arm -> forearm -> tool0 (flange) -> tcp.
+    /// Pitch axes are -y of the link frames, matching `UrdfModel::to_robot_arm`.
+    ///
+    /// Re-importing gives the same kinematics and position/rate limits, but URDF
+    /// has no acceleration limit, so `max_acc_rad_s2` comes back unlimited; the drives
+    /// are written for other tools and not read back either.
+    pub fn write_urdf<W: Write>(&self, name: &str, mut w: W) -> io::Result<()> {
+        let limits = self.joint_limits();
+        let actuators = self.actuators();
+
+        writeln!(w, r#"<?xml version="1.0"?>"#)?;
+        writeln!(w, r#"<robot name="{name}">"#)?;
+        writeln!(w, r#"  <link name="base_link"/>"#)?;
+        writeln!(w, r#"  <link name="yaw_link"/>"#)?;
+        write_urdf_link(&mut w, "upper_arm", self.link1())?;
+        write_urdf_link(&mut w, "forearm", self.link2())?;
+        writeln!(w, r#"  <link name="tool0"/>"#)?;
+        writeln!(w, r#"  <link name="tcp"/>"#)?;
+
+        let joints = [
+            ("base_link", "yaw_link", *self.base(), "0 0 1"),
+            ("yaw_link", "upper_arm", Transform::IDENTITY, "0 -1 0"),
+            ("upper_arm", "forearm", Transform::from_translation(self.l1(), 0.0, 0.0), "0 -1 0"),
+        ];
+        for (i, (parent, child, origin, axis)) in joints.into_iter().enumerate() {
+            let lim = &limits[i];
+            let kind = if is_bounded(lim) { "revolute" } else { "continuous" };
+            writeln!(w, r#"  <joint name="{}" type="{kind}">"#, JOINT_NAMES[i])?;
+            writeln!(w, r#"    <parent link="{parent}"/>"#)?;
+            writeln!(w, r#"    <child link="{child}"/>"#)?;
+            writeln!(w, "    {}", urdf_origin(&origin))?;
+            writeln!(w, r#"    <axis xyz="{axis}"/>"#)?;
+            let act = &actuators[i];
+            let velocity = finite_or(lim.max_vel_rad_s, UNLIMITED);
+            let effort = finite_or(act.max_joint_torque(), UNLIMITED);
+            if is_bounded(lim) {
+                writeln!(
+                    w,
+                    r#"    <limit lower="{}" upper="{}" effort="{effort}" velocity="{velocity}"/>"#,
+                    lim.min_rad, lim.max_rad
+                )?;
+            } else {
+                writeln!(w, r#"    <limit effort="{effort}" velocity="{velocity}"/>"#)?;
+            }
+            if act.viscous_nm_s_per_rad > 0.0 || act.coulomb_nm > 0.0 {
+                writeln!(
+                    w,
+                    r#"    <dynamics damping="{}" friction="{}"/>"#,
+                    act.viscous_nm_s_per_rad, act.coulomb_nm
+                )?;
+            }
+            writeln!(w, "  </joint>")?;
+        }
+
+        let flange = Transform::from_translation(self.l2(), 0.0, 0.0) * flange_rotation();
+        for (name, parent, child, origin) in [("flange", "forearm", "tool0", flange), ("tool", "tool0", "tcp", *self.tcp())] {
+            writeln!(w, r#"  <joint name="{name}" type="fixed">"#)?;
+            writeln!(w, r#"    <parent link="{parent}"/>"#)?;
+            writeln!(w, r#"    <child link="{child}"/>"#)?;
+            writeln!(w, "    {}", urdf_origin(&origin))?;
+            writeln!(w, "  </joint>")?;
+        }
+
+        writeln!(w, "</robot>")
+    }
+
+    pub fn save_urdf(&self, name: &str, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.write_urdf(name, io::BufWriter::new(file))
+    }
+
+    /// MuJoCo MJCF of the arm. Yaw and shoulder share the `upper_arm` body (MuJoCo
+    /// chains joints of one body in order); the TCP is the site `tcp` in body `tool0`.
+    /// Reflected rotor inertia and joint friction become `armature`, `damping` and `frictionloss`.
+    pub fn write_mjcf<W: Write>(&self, name: &str, mut w: W) -> io::Result<()> {
+        let limits = self.joint_limits();
+        let actuators = self.actuators();
+        let joint = |i: usize, axis: &str| {
+            let lim = &limits[i];
+            let mut attrs = if is_bounded(lim) {
+                format!(r#" range="{} {}""#, lim.min_rad, lim.max_rad)
+            } else {
+                String::new()
+            };
+            let act = &actuators[i];
+            for (attr, v) in [
+                ("armature", act.reflected_inertia()),
+                ("damping", act.viscous_nm_s_per_rad),
+                ("frictionloss", act.coulomb_nm),
+            ] {
+                if v > 0.0 {
+                    attrs += &format!(r#" {attr}="{v}""#);
+                }
+            }
+            format!(r#"<joint name="{}" type="hinge" axis="{axis}"{attrs}/>"#, JOINT_NAMES[i])
+        };
+        let base = self.base();
+        let flange = flange_rotation();
+        let tcp = self.tcp();
+
+        writeln!(w, r#"<mujoco model="{name}">"#)?;
+        writeln!(w, r#"  <compiler angle="radian" autolimits="true"/>"#)?;
+        writeln!(w, r#"  <option gravity="0 0 -9.81"/>"#)?;
+        writeln!(w, "  <worldbody>")?;
+        writeln!(w, r#"    <body name="upper_arm" {}>"#, mjcf_pose(base))?;
+        writeln!(w, "      {}", joint(0, "0 0 1"))?;
+        writeln!(w, "      {}", joint(1, "0 -1 0"))?;
+        write_mjcf_link(&mut w, "      ", self.link1())?;
+        writeln!(w, r#"      <body name="forearm" pos="{} 0 0">"#, self.l1())?;
+        writeln!(w, "        {}", joint(2, "0 -1 0"))?;
+        write_mjcf_link(&mut w, "        ", self.link2())?;
+        writeln!(w, r#"        <body name="tool0" pos="{} 0 0" {}>"#, self.l2(), mjcf_quat(&flange.rotation))?;
+        writeln!(w, r#"          <site name="tcp" {} size="0.02"/>"#, mjcf_pose(tcp))?;
+        writeln!(w, "        </body>")?;
+        writeln!(w, "      </body>")?;
+        writeln!(w, "    </body>")?;
+        writeln!(w, "  </worldbody>")?;
+        writeln!(w, "</mujoco>")
+    }
+
+    pub fn save_mjcf(&self, name: &str, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.write_mjcf(name, io::BufWriter::new(file))
+    }
+}
+
+fn is_bounded(lim: &JointLimits) -> bool {
+    lim.min_rad.is_finite() && lim.max_rad.is_finite()
+}
+
+fn finite_or(v: f64, fallback: f64) -> f64 {
+    if v.is_finite() {
+        v
+    } else {
+        fallback
+    }
+}
+
+fn write_urdf_link<W: Write>(w: &mut W, name: &str, link: &LinkParams) -> io::Result<()> {
+    if link.mass_kg <= 0.0 {
+        return writeln!(w, r#"  <link name="{name}"/>"#);
+    }
+    let (com, [ixx, iyy, izz]) = (link.com_offset_m(), link.principal_inertia());
+    writeln!(w, r#"  <link name="{name}">"#)?;
+    writeln!(w, "    <inertial>")?;
+    writeln!(w, r#"      <origin xyz="{com} 0 0"/>"#)?;
+    writeln!(w, r#"      <mass value="{}"/>"#, link.mass_kg)?;
+    writeln!(w, r#"      <inertia ixx="{ixx}" ixy="0" ixz="0" iyy="{iyy}" iyz="0" izz="{izz}"/>"#)?;
+    writeln!(w, "    </inertial>")?;
+    writeln!(w, "  </link>")
+}
+
+fn write_mjcf_link<W: Write>(w: &mut W, indent: &str, link: &LinkParams) -> io::Result<()> {
+    if link.mass_kg > 0.0 {
+        let (com, [ixx, iyy, izz]) = (link.com_offset_m(), link.principal_inertia());
+        writeln!(
+            w,
+            r#"{indent}<inertial pos="{com} 0 0" mass="{}" diaginertia="{ixx} {iyy} {izz}"/>"#,
+            link.mass_kg
+        )?;
+    }
+    // Visual only: the explicit inertial above defines the mass
+    writeln!(
+        w,
+        r#"{indent}<geom type="capsule" fromto="0 0 0 {} 0 0" size="0.06" contype="0" conaffinity="0"/>"#,
+        link.length_m
+    )
+}
+
+/// `<origin xyz rpy>` with URDF's fixed-axis roll-pitch-yaw (R = Rz(y) Ry(p) Rx(r)).
+fn urdf_origin(t: &Transform) -> String {
+    let p = t.translation;
+    let [r, pitch, y] = rpy(&t.rotation);
+    format!(r#"<origin xyz="{} {} {}" rpy="{r} {pitch} {y}"/>"#, p.x, p.y, p.z)
+}
+
+fn rpy(m: &Mat3) -> [f64; 3] {
+    let pitch = (-m[2][0]).clamp(-1.0, 1.0).asin();
+    if m[2][0].abs() < 1.0 - 1e-12 {
+        [m[2][1].atan2(m[2][2]), pitch, m[1][0].atan2(m[0][0])]
+    } else {
+        // Gimbal lock: only roll +- yaw is defined, put it all in yaw
+        [0.0, pitch, (-m[0][1]).atan2(m[1][1])]
+    }
+}
+
+fn mjcf_pose(t: &Transform) -> String {
+    let p = t.translation;
+    format!(r#"pos="{} {} {}" {}"#, p.x, p.y, p.z, mjcf_quat(&t.rotation))
+}
+
+/// MuJoCo `quat="w x y z"` of a rotation matrix.
+fn mjcf_quat(m: &Mat3) -> String {
+    let [w, x, y, z] = quaternion(m);
+    format!(r#"quat="{w} {x} {y} {z}""#)
+}
+
+fn quaternion(m: &Mat3) -> [f64; 4] {
+    let tr = m[0][0] + m[1][1] + m[2][2];
+    // Branch on the largest diagonal term for accuracy
+    if tr > 0.0 {
+        let s = (tr + 1.0).sqrt() * 2.0;
+        [0.25 * s, (m[2][1] - m[1][2]) / s, (m[0][2] - m[2][0]) / s, (m[1][0] - m[0][1]) / s]
+    } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+        let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+        [(m[2][1] - m[1][2]) / s, 0.25 * s, (m[0][1] + m[1][0]) / s, (m[0][2] + m[2][0]) / s]
+    } else if m[1][1] > m[2][2] {
+        let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+        [(m[0][2] - m[2][0]) / s, (m[0][1] + m[1][0]) / s, 0.25 * s, (m[1][2] + m[2][1]) / s]
+    } else {
+        let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+        [(m[1][0] - m[0][1]) / s, (m[0][2] + m[2][0]) / s, (m[1][2] + m[2][1]) / s, 0.25 * s]
+    }
+}
@@ -1,5 +1,29 @@
-#![allow(unused_imports)]
-
+pub mod actuator;
+pub mod calibration;
+pub mod dynamics;
+pub mod export;
+pub mod math;
+pub mod numeric_ik;
 pub mod robot_arm;
+pub mod serial_chain;
+pub mod transform;
+#[cfg(feature = "urdf")]
+pub mod urdf;
 
-pub use robot_arm::{FKResult, IKResult, JointAngles, LinkParams, RobotArm};
+pub use robot_arm::{
+    max_joint_step, FKResult, IKContinuity, IKResult, IKSolution, JacobianMetrics, JointAngles, JointConvention,
+    JointLimits, LimitViolation, LinkParams, RobotArm, SingularityKind, SingularityProximity, SingularityThresholds,
+    WorkspaceProjection, JOINT_NAMES,
+};
+pub use actuator::ActuatorParams;
+pub use calibration::{
+    calibrate, load_measurements, read_measurements, Calibration, CalibrationError, CalibrationParams,
+    KinematicParams, Measurement,
+};
+pub use dynamics::{Payload, TorqueBreakdown, TorqueStats, GRAVITY};
+pub use math::{Mat3, Vec3};
+pub use numeric_ik::{dls_step, solve_dls, DlsParams, DlsSolution, IKDiagnostics};
+pub use serial_chain::{DhConvention, DhRow, JointType, SerialChain};
+pub use transform::Transform;
+#[cfg(feature = "urdf")]
+pub use urdf::{UrdfChain, UrdfError, UrdfJoint, UrdfJointType, UrdfModel};
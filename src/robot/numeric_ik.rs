@@ -0,0 +1,202 @@
+use super::math::{Mat3, Vec3};
+use super::serial_chain::SerialChain;
+
+/// Settings for the damped-least-squares (Levenberg–Marquardt) IK solver.
+#[derive(Copy, Clone, Debug)]
+pub struct DlsParams {
+    pub max_iterations: usize,
+    /// Stop once the weighted task error norm drops below this
+    pub tolerance: f64,
+    /// Initial damping lambda; adapted up/down as the error grows/shrinks
+    pub damping: f64,
+    /// Largest joint update norm per iteration (rad or m)
+    pub max_step: f64,
+    /// Weight of the orientation error (rad) relative to position error (m)
+    pub orientation_weight: f64,
+}
+
+impl Default for DlsParams {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            tolerance: 1e-6,
+            damping: 0.05,
+            max_step: 0.5,
+            orientation_weight: 1.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct IKDiagnostics {
+    pub iterations: usize,
+    /// Final weighted task error norm
+    pub residual: f64,
+    pub converged: bool,
+    /// Damping in use when the solver stopped
+    pub final_damping: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DlsSolution {
+    pub q: Vec<f64>,
+    pub diagnostics: IKDiagnostics,
+}
+
+/// Iteratively solve for joint values placing the tool frame of `chain` at `target_pos`
+/// and, when given, aligning its rotation with `target_rot`. Joint values are kept
+/// inside each row's position limits.
+pub fn solve_dls(
+    chain: &SerialChain,
+    target_pos: Vec3,
+    target_rot: Option<Mat3>,
+    seed: &[f64],
+    params: &DlsParams,
+) -> DlsSolution {
+    let n = chain.dof();
+    let mut q: Vec<f64> = (0..n).map(|i| seed.get(i).copied().unwrap_or(0.0)).collect();
+    clamp_to_limits(chain, &mut q);
+
+    let mut lambda = params.damping.max(1e-9);
+    let mut err = task_error(chain, &q, target_pos, target_rot, params.orientation_weight);
+    let mut err_norm = norm(&err);
+
+    let mut diag = IKDiagnostics::default();
+
+    for it in 0..params.max_iterations {
+        if err_norm < params.tolerance {
+            diag.converged = true;
+            break;
+        }
+        diag.iterations = it + 1;
+
+        let jac = task_jacobian(chain, &q, target_rot.is_some(), params.orientation_weight);
+        let mut dq = dls_step(&jac, &err, lambda);
+
+        let step = norm(&dq);
+        if step > params.max_step {
+            let s = params.max_step / step;
+            dq.iter_mut().for_each(|v| *v *= s);
+        }
+
+        let mut q_new: Vec<f64> = q.iter().zip(&dq).map(|(a, b)| a + b).collect();
+        clamp_to_limits(chain, &mut q_new);
+
+        let err_new = task_error(chain, &q_new, target_pos, target_rot, params.orientation_weight);
+        let err_new_norm = norm(&err_new);
+
+        if err_new_norm < err_norm {
+            q = q_new;
+            err = err_new;
+            err_norm = err_new_norm;
+            lambda = (lambda * 0.5).max(1e-9);
+        } else {
+            lambda *= 4.0;
+            if lambda > 1e6 {
+                break;
+            }
+        }
+    }
+
+    if err_norm < params.tolerance {
+        diag.converged = true;
+    }
+    diag.residual = err_norm;
+    diag.final_damping = lambda;
+
+    DlsSolution { q, diagnostics: diag }
+}
+
+/// Damped least-squares update `J^T (J J^T + lambda^2 I)^-1 e` for a row-major
+/// `m x n` Jacobian. Stays bounded near singularities.
+pub fn dls_step(jac: &[Vec<f64>], e: &[f64], damping: f64) -> Vec<f64> {
+    let m = jac.len();
+    let n = jac.first().map(|r| r.len()).unwrap_or(0);
+
+    let mut a = vec![vec![0.0_f64; m]; m];
+    for r in 0..m {
+        for c in 0..m {
+            a[r][c] = (0..n).map(|k| jac[r][k] * jac[c][k]).sum();
+        }
+        a[r][r] += damping * damping;
+    }
+
+    let Some(y) = solve_linear(a, e.to_vec()) else {
+        return vec![0.0; n];
+    };
+
+    (0..n).map(|k| (0..m).map(|r| jac[r][k] * y[r]).sum()).collect()
+}
+
+fn task_error(
+    chain: &SerialChain,
+    q: &[f64],
+    target_pos: Vec3,
+    target_rot: Option<Mat3>,
+    orientation_weight: f64,
+) -> Vec<f64> {
+    let ee = chain.end_effector(q);
+    let d = target_pos - ee.translation;
+    let mut e = vec![d.x, d.y, d.z];
+
+    if let Some(desired) = target_rot {
+        // 0.5 * sum_i (x_i x x_i_desired) over the frame axes
+        let mut w = Vec3::ZERO;
+        for i in 0..3 {
+            w += ee.axis(i).cross(desired.column(i)) * 0.5;
+        }
+        e.extend(w.to_array().iter().map(|v| v * orientation_weight));
+    }
+    e
+}
+
+fn task_jacobian(chain: &SerialChain, q: &[f64], with_orientation: bool, orientation_weight: f64) -> Vec<Vec<f64>> {
+    let cols = chain.jacobian(q);
+    let rows = if with_orientation { 6 } else { 3 };
+
+    (0..rows)
+        .map(|r| {
+            let w = if r >= 3 { orientation_weight } else { 1.0 };
+            cols.iter().map(|c| c[r] * w).collect()
+        })
+        .collect()
+}
+
+fn clamp_to_limits(chain: &SerialChain, q: &mut [f64]) {
+    for (qi, row) in q.iter_mut().zip(chain.rows()) {
+        *qi = qi.clamp(row.limits.min_rad, row.limits.max_rad);
+    }
+}
+
+fn norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Gaussian elimination with partial pivoting. `None` if `a` is singular.
+pub(crate) fn solve_linear(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-14 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let pivot_row = a[col].clone();
+        for r in (col + 1)..n {
+            let f = a[r][col] / pivot_row[col];
+            for (x, p) in a[r][col..].iter_mut().zip(&pivot_row[col..]) {
+                *x -= f * p;
+            }
+            b[r] -= f * b[col];
+        }
+    }
+
+    let mut x = vec![0.0_f64; n];
+    for r in (0..n).rev() {
+        let s: f64 = ((r + 1)..n).map(|c| a[r][c] * x[c]).sum();
+        x[r] = (b[r] - s) / a[r][r];
+    }
+    Some(x)
+}
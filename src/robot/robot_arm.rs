@@ -1,11 +1,80 @@
-use raylib::prelude::*;
+use super::actuator::ActuatorParams;
+use super::dynamics::Payload;
+use super::math::{Mat3, Vec3};
+use super::numeric_ik::{solve_dls, solve_linear, DlsParams, IKDiagnostics};
+use super::serial_chain::{DhConvention, DhRow, SerialChain};
+use super::transform::Transform;
+
+pub const JOINT_NAMES: [&str; 3] = ["q0_yaw", "q1_pitch", "q2_pitch"];
+
+/// Distance `project_to_workspace` keeps from the workspace boundary (m).
+pub const PROJECTION_CLEARANCE_M: f64 = 2e-3;
+
+/// Radius of the solid rod assumed for the (unmodelled) inertia about a link's own axis.
+const ROD_RADIUS_M: f64 = 0.05;
+
+/// Range constraints of one actuated joint. Defaults to unlimited.
+#[derive(Copy, Clone, Debug)]
+pub struct JointLimits {
+    pub min_rad: f64,
+    pub max_rad: f64,
+    pub max_vel_rad_s: f64,
+    pub max_acc_rad_s2: f64,
+}
+
+impl Default for JointLimits {
+    fn default() -> Self {
+        Self {
+            min_rad: f64::NEG_INFINITY,
+            max_rad: f64::INFINITY,
+            max_vel_rad_s: f64::INFINITY,
+            max_acc_rad_s2: f64::INFINITY,
+        }
+    }
+}
+
+impl JointLimits {
+    pub fn contains(&self, q: f64) -> bool {
+        q >= self.min_rad && q <= self.max_rad
+    }
+}
+
+/// Encoder convention of one joint relative to the model: `model = sign * hardware + zero_offset_rad`,
+/// with `sign = -1` for an inverted joint. Defaults to the model convention.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct JointConvention {
+    /// Model angle at which the encoder reads zero
+    pub zero_offset_rad: f64,
+    /// Encoder counts up where the model angle decreases
+    pub inverted: bool,
+}
+
+impl JointConvention {
+    pub fn sign(&self) -> f64 {
+        if self.inverted {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    pub fn to_model(&self, hardware: f64) -> f64 {
+        self.sign() * hardware + self.zero_offset_rad
+    }
+
+    pub fn to_hardware(&self, model: f64) -> f64 {
+        self.sign() * (model - self.zero_offset_rad)
+    }
+}
 
 #[derive(Copy, Clone, Debug)]
 pub struct LinkParams {
-    pub length_m: f32,
-    pub mass_kg: f32,
-    pub inertia_cm: f32,
-    pub inertia_joint: f32,
+    pub length_m: f64,
+    pub mass_kg: f64,
+    pub inertia_cm: f64,
+    pub inertia_joint: f64,
+    /// Limits of the joint driving this link
+    pub limits: JointLimits,
 }
 
 impl Default for LinkParams {
@@ -15,6 +84,7 @@ impl Default for LinkParams {
             mass_kg: 1.0,
             inertia_cm: 0.0,
             inertia_joint: 0.0,
+            limits: JointLimits::default(),
         }
     }
 }
@@ -25,77 +95,665 @@ impl LinkParams {
         self.inertia_cm = (1.0 / 12.0) * self.mass_kg * self.length_m * self.length_m;
         self.inertia_joint = (1.0 / 3.0) * self.mass_kg * self.length_m * self.length_m;
     }
+
+    /// COM distance from the driving joint along the link, from the parallel-axis gap
+    /// between `inertia_joint` and `inertia_cm`.
+    pub fn com_offset_m(&self) -> f64 {
+        if self.mass_kg > 0.0 {
+            ((self.inertia_joint - self.inertia_cm) / self.mass_kg).max(0.0).sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    /// Principal inertias about the COM in the link frame (x along the link).
+    /// The axial term assumes a solid rod of 5 cm radius.
+    pub fn principal_inertia(&self) -> [f64; 3] {
+        let axial = 0.5 * self.mass_kg * ROD_RADIUS_M * ROD_RADIUS_M;
+        [axial, self.inertia_cm, self.inertia_cm]
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct JointAngles {
-    pub q0_yaw: f32,
-    pub q1_pitch: f32,
-    pub q2_pitch: f32,
+    pub q0_yaw: f64,
+    pub q1_pitch: f64,
+    pub q2_pitch: f64,
+}
+
+impl JointAngles {
+    pub fn to_array(self) -> [f64; 3] {
+        [self.q0_yaw, self.q1_pitch, self.q2_pitch]
+    }
+}
+
+impl From<[f64; 3]> for JointAngles {
+    fn from([q0_yaw, q1_pitch, q2_pitch]: [f64; 3]) -> Self {
+        Self { q0_yaw, q1_pitch, q2_pitch }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct FKResult {
-    pub base: Vector3,
-    pub joint1: Vector3,
-    pub joint2: Vector3,
-    pub ee: Vector3,
+    pub base: Vec3,
+    pub joint1: Vec3,
+    pub joint2: Vec3,
+    /// Wrist / tool flange at the end of link 2
+    pub ee: Vec3,
+    /// Tool center point (`ee` displaced by the arm's TCP transform)
+    pub tcp: Vec3,
+    /// World pose of the DH frames: 0 = base (yaw about z), 1 = shoulder (pitch about z),
+    /// 2 = elbow (pitch about z, x along link 2), 3 = flange (x along link 2)
+    pub frames: [Transform; 4],
+    /// World pose of the TCP frame
+    pub tool: Transform,
 }
 
-#[derive(Clone, Debug)]
+impl FKResult {
+    /// Tool approach direction (x axis of the TCP frame).
+    pub fn approach(&self) -> Vec3 {
+        self.tool.axis(0)
+    }
+
+    /// Rotation axis of joint `i` (0 = yaw, 1 = shoulder, 2 = elbow) in the world.
+    pub fn joint_axis(&self, i: usize) -> Vec3 {
+        self.frames[i].axis(2)
+    }
+}
+
+/// Joint position limit violated by an IK solution.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LimitViolation {
+    /// 0 = yaw, 1 = shoulder, 2 = elbow
+    pub joint: usize,
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl std::fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} = {:.3} rad outside joint limit [{:.3}, {:.3}]",
+            JOINT_NAMES[self.joint], self.value, self.min, self.max
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct IKResult {
     pub reachable: bool,
     pub q: JointAngles,
     pub message: String,
+    /// Set when the geometric solution exists but breaks a joint limit
+    pub violation: Option<LimitViolation>,
+    /// Convergence info from the iterative solver (`None` for closed-form results)
+    pub diagnostics: Option<IKDiagnostics>,
+    /// Step from the seed (`solve_ik_continuous` only)
+    pub continuity: Option<IKContinuity>,
+}
+
+/// Joint-space step taken by `RobotArm::solve_ik_continuous` from its seed.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct IKContinuity {
+    /// Largest absolute joint change from the previous angles (rad per tick)
+    pub max_step_rad: f64,
+    /// Joint with the largest change (0 = yaw, 1 = shoulder, 2 = elbow)
+    pub max_step_joint: usize,
+    /// Elbow changed sides because its previous side had no solution within limits
+    pub branch_switched: bool,
+}
+
+/// One IK branch for a target, as returned by `RobotArm::solve_ik_all`.
+#[derive(Copy, Clone, Debug)]
+pub struct IKSolution {
+    pub q: JointAngles,
+    /// Same elbow placement as `solve_ik(target, elbow_up)`
+    pub elbow_up: bool,
+    /// Reach-over-the-back branch: q0 + pi, q1 mirrored to pi - q1, q2 negated
+    pub shoulder_flip: bool,
+    /// Joint-space distance to the seed (yaw difference wrapped to [-pi, pi])
+    pub distance: f64,
+    pub within_limits: bool,
+}
+
+/// Closest reachable position to a requested target, from `RobotArm::project_to_workspace`.
+#[derive(Copy, Clone, Debug)]
+pub struct WorkspaceProjection {
+    pub point: Vec3,
+    /// Joint angles reaching `point` within all limits
+    pub q: JointAngles,
+    /// Distance from the requested target to `point` (0 when already reachable)
+    pub error_m: f64,
+}
+
+/// Conditioning of the linear-velocity Jacobian at a configuration.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JacobianMetrics {
+    pub determinant: f64,
+    /// sigma_max / sigma_min (infinite at a singularity)
+    pub condition_number: f64,
+    /// Yoshikawa index sqrt(det(J J^T))
+    pub manipulability: f64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SingularityKind {
+    /// TCP on the yaw axis: yaw is undefined and yaw rates blow up
+    BaseAxis,
+    /// Elbow straight (outer workspace boundary)
+    ElbowStretched,
+    /// Elbow folded back onto link 1 (inner workspace boundary)
+    ElbowFolded,
+}
+
+impl SingularityKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SingularityKind::BaseAxis => "base axis",
+            SingularityKind::ElbowStretched => "elbow stretched",
+            SingularityKind::ElbowFolded => "elbow folded",
+        }
+    }
+}
+
+/// Distances below which a configuration counts as near-singular.
+#[derive(Copy, Clone, Debug)]
+pub struct SingularityThresholds {
+    pub base_axis_m: f64,
+    pub elbow_rad: f64,
 }
 
-impl Default for IKResult {
+impl Default for SingularityThresholds {
     fn default() -> Self {
         Self {
-            reachable: false,
-            q: JointAngles::default(),
-            message: String::new(),
+            base_axis_m: 0.25,
+            elbow_rad: 8.0_f64.to_radians(),
         }
     }
 }
 
+/// How far a configuration is from each singularity of the arm.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SingularityProximity {
+    /// TCP distance from the yaw axis
+    pub base_axis_m: f64,
+    /// Effective elbow angle (to the TCP) away from straight
+    pub stretched_rad: f64,
+    /// Effective elbow angle away from fully folded
+    pub folded_rad: f64,
+}
+
+impl SingularityProximity {
+    /// Normalised margin: 1 at or beyond the thresholds, 0 at a singularity.
+    pub fn margin(&self, th: &SingularityThresholds) -> f64 {
+        let base = self.base_axis_m / th.base_axis_m.max(1e-6);
+        let elbow = self.stretched_rad.min(self.folded_rad) / th.elbow_rad.max(1e-6);
+        base.min(elbow).clamp(0.0, 1.0)
+    }
+
+    /// Closest singularity inside the thresholds, if any.
+    pub fn nearest(&self, th: &SingularityThresholds) -> Option<SingularityKind> {
+        let base = self.base_axis_m / th.base_axis_m.max(1e-6);
+        let stretched = self.stretched_rad / th.elbow_rad.max(1e-6);
+        let folded = self.folded_rad / th.elbow_rad.max(1e-6);
+
+        [
+            (base, SingularityKind::BaseAxis),
+            (stretched, SingularityKind::ElbowStretched),
+            (folded, SingularityKind::ElbowFolded),
+        ]
+        .into_iter()
+        .filter(|(r, _)| *r < 1.0)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, kind)| kind)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct RobotArm {
     link1: LinkParams,
     link2: LinkParams,
+    yaw_limits: JointLimits,
+    conventions: [JointConvention; 3],
+    actuators: [ActuatorParams; 3],
+    tcp: Transform,
+    base: Transform,
+    payload: Option<Payload>,
 }
 
 impl RobotArm {
+    /// Arm with uniform-rod inertias computed from each link's length and mass.
     pub fn new(mut l1: LinkParams, mut l2: LinkParams) -> Self {
         l1.recompute_inertia();
         l2.recompute_inertia();
-        Self { link1: l1, link2: l2 }
+        Self::from_links(l1, l2)
+    }
+
+    /// Arm that keeps the links' `inertia_cm` / `inertia_joint` as given (e.g. from URDF).
+    pub fn from_links(l1: LinkParams, l2: LinkParams) -> Self {
+        Self {
+            link1: l1,
+            link2: l2,
+            yaw_limits: JointLimits::default(),
+            conventions: [JointConvention::default(); 3],
+            actuators: [ActuatorParams::IDEAL; 3],
+            tcp: Transform::IDENTITY,
+            base: Transform::IDENTITY,
+            payload: None,
+        }
+    }
+
+    /// Mounting pose of the base frame in the world (floor, wall or ceiling).
+    pub fn with_base(mut self, base: Transform) -> Self {
+        self.base = base;
+        self
+    }
+
+    pub fn base(&self) -> &Transform { &self.base }
+
+    /// Tool center point relative to the flange frame at the end of link 2
+    /// (x along link 2, y in the arm plane, z along the elbow axis).
+    pub fn with_tcp(mut self, tcp: Transform) -> Self {
+        self.tcp = tcp;
+        self
+    }
+
+    pub fn tcp(&self) -> &Transform { &self.tcp }
+
+    /// Attach (`Some`) or release (`None`) a load at the TCP. Only the dynamics see it.
+    pub fn set_payload(&mut self, payload: Option<Payload>) {
+        self.payload = payload;
+    }
+
+    pub fn payload(&self) -> Option<&Payload> { self.payload.as_ref() }
+
+    /// Distance of a world point from the base origin (compare with `min_reach`/`max_reach`).
+    pub fn distance_from_base(&self, p: Vec3) -> f64 {
+        p.distance(self.base.translation)
+    }
+
+    pub fn with_yaw_limits(mut self, limits: JointLimits) -> Self {
+        self.yaw_limits = limits;
+        self
+    }
+
+    /// Cable-wrap limit of the yaw joint: `turns` full turns either way from zero.
+    pub fn with_cable_wrap(mut self, turns: f64) -> Self {
+        let span = turns.abs() * std::f64::consts::TAU;
+        self.yaw_limits.min_rad = -span;
+        self.yaw_limits.max_rad = span;
+        self
+    }
+
+    /// Encoder conventions indexed like `JointAngles`. Limits, IK and FK stay in model angles.
+    pub fn with_joint_conventions(mut self, conventions: [JointConvention; 3]) -> Self {
+        self.conventions = conventions;
+        self
+    }
+
+    pub fn joint_conventions(&self) -> [JointConvention; 3] { self.conventions }
+
+    /// Drives indexed like `JointAngles`; their reflected inertia enters the dynamics.
+    pub fn with_actuators(mut self, actuators: [ActuatorParams; 3]) -> Self {
+        self.actuators = actuators;
+        self
+    }
+
+    pub fn actuators(&self) -> &[ActuatorParams; 3] { &self.actuators }
+
+    /// Model angles as the hardware would read them.
+    pub fn to_hardware(&self, q: JointAngles) -> JointAngles {
+        let [c0, c1, c2] = &self.conventions;
+        JointAngles {
+            q0_yaw: c0.to_hardware(q.q0_yaw),
+            q1_pitch: c1.to_hardware(q.q1_pitch),
+            q2_pitch: c2.to_hardware(q.q2_pitch),
+        }
+    }
+
+    /// Hardware (encoder) angles in the model convention.
+    pub fn from_hardware(&self, q: JointAngles) -> JointAngles {
+        let [c0, c1, c2] = &self.conventions;
+        JointAngles {
+            q0_yaw: c0.to_model(q.q0_yaw),
+            q1_pitch: c1.to_model(q.q1_pitch),
+            q2_pitch: c2.to_model(q.q2_pitch),
+        }
     }
 
     pub fn link1(&self) -> &LinkParams { &self.link1 }
     pub fn link2(&self) -> &LinkParams { &self.link2 }
 
-    pub fn l1(&self) -> f32 { self.link1.length_m }
-    pub fn l2(&self) -> f32 { self.link2.length_m }
+    /// Limits indexed like `JointAngles`: yaw, shoulder, elbow.
+    pub fn joint_limits(&self) -> [JointLimits; 3] {
+        [self.yaw_limits, self.link1.limits, self.link2.limits]
+    }
+
+    pub fn l1(&self) -> f64 { self.link1.length_m }
+    pub fn l2(&self) -> f64 { self.link2.length_m }
+
+    /// Largest TCP distance from the base.
+    pub fn max_reach(&self) -> f64 {
+        let (l2, _, tz) = self.tool_link();
+        (self.l1() + l2).hypot(tz)
+    }
+
+    /// Smallest TCP distance from the base.
+    pub fn min_reach(&self) -> f64 {
+        let (l2, _, tz) = self.tool_link();
+        (self.l1() - l2).abs().hypot(tz)
+    }
+
+    /// Link 2 extended to the TCP: in-plane length, angle offset from link 2,
+    /// and out-of-plane offset along the elbow axis.
+    fn tool_link(&self) -> (f64, f64, f64) {
+        let [tx, ty, tz] = self.tcp.translation.to_array();
+        let ax = self.l2() + tx;
+        (ax.hypot(ty), ty.atan2(ax), tz)
+    }
+
+    /// Standard-DH model of this arm for the generic `SerialChain` code paths.
+    /// `solve_ik`/`forward_kinematics` remain the closed-form fast path.
+    pub fn serial_chain(&self) -> SerialChain {
+        let l1 = self.l1();
+        let l2 = self.l2();
+
+        let mut rows = vec![
+            DhRow::revolute(0.0, std::f64::consts::FRAC_PI_2, 0.0, 0.0),
+            DhRow::revolute(l1, 0.0, 0.0, 0.0),
+            DhRow::revolute(l2, 0.0, 0.0, 0.0),
+        ];
+        for (row, limits) in rows.iter_mut().zip(self.joint_limits()) {
+            row.limits = limits;
+        }
+        SerialChain::new(DhConvention::Standard, rows)
+            .with_base(self.base)
+            .with_tool(self.tcp)
+    }
+
+    /// Closed-form IK placing the TCP at `target` for one elbow branch. A solution outside
+    /// the joint limits is rejected with `violation` set (`q` still holds the offending angles).
+    pub fn solve_ik(&self, target: Vec3, elbow_up: bool) -> IKResult {
+        let mut out = self.solve_ik_branch(target, elbow_up, false);
+        if out.reachable {
+            if let Some(v) = self.check_limits(out.q) {
+                out.reachable = false;
+                out.message = v.to_string();
+                out.violation = Some(v);
+            }
+        }
+        out
+    }
+
+    /// Like `solve_ik`, but when the requested branch breaks a joint limit the
+    /// nearest other branch that respects all limits is returned instead.
+    pub fn solve_ik_preferred(&self, target: Vec3, elbow_up: bool) -> IKResult {
+        let first = self.solve_ik(target, elbow_up);
+        if first.violation.is_none() {
+            return first;
+        }
+
+        match self.solve_ik_all(target, first.q).into_iter().find(|s| s.within_limits) {
+            Some(s) => IKResult {
+                reachable: true,
+                q: s.q,
+                message: "OK".to_string(),
+                violation: None,
+                diagnostics: None,
+                continuity: None,
+            },
+            None => first,
+        }
+    }
+
+    /// IK that continues from `prev` (the last commanded angles): yaw is unwrapped to the
+    /// turn nearest `prev`, and the elbow stays on the same side unless only the other side
+    /// has a solution within limits. When the nearest solution runs into the cable wrap the
+    /// yaw limit is reported instead of unwinding a turn or flipping the shoulder.
+    pub fn solve_ik_continuous(&self, target: Vec3, prev: JointAngles) -> IKResult {
+        let prev_side = self.elbow_side(prev);
+
+        let mut best: Option<(bool, f64, JointAngles)> = None;
+        let mut nearest: Option<(bool, f64, Option<LimitViolation>)> = None;
+        let mut violation = None;
+
+        for s in self.solve_ik_all(target, prev) {
+            let mut q = s.q;
+            q.q0_yaw = prev.q0_yaw + wrap_angle(q.q0_yaw - prev.q0_yaw);
 
-    pub fn max_reach(&self) -> f32 { self.link1.length_m + self.link2.length_m }
-    pub fn min_reach(&self) -> f32 { (self.link1.length_m - self.link2.length_m).abs() }
+            let side = self.elbow_side(q);
+            let switched = prev_side != 0 && side != 0 && side != prev_side;
+            let (_, step) = max_joint_step(prev, q);
+            let limit = self.check_limits(q);
+            if nearest.is_none_or(|(b_switched, b_step, _)| (switched, step) < (b_switched, b_step)) {
+                nearest = Some((switched, step, limit));
+            }
 
-    pub fn solve_ik(&self, target: Vector3, elbow_up: bool) -> IKResult {
+            if let Some(v) = limit {
+                violation.get_or_insert(v);
+                continue;
+            }
+
+            let better = match best {
+                None => true,
+                Some((b_switched, b_step, _)) => (switched, step) < (b_switched, b_step),
+            };
+            if better {
+                best = Some((switched, step, q));
+            }
+        }
+
+        // Cable wrap reached: stop rather than jump to another turn or branch
+        if let Some((_, _, Some(v))) = nearest {
+            if v.joint == 0 {
+                best = None;
+                violation = Some(v);
+            }
+        }
+
+        let Some((branch_switched, _, q)) = best else {
+            return match violation {
+                Some(v) => IKResult {
+                    message: v.to_string(),
+                    violation: Some(v),
+                    ..Default::default()
+                },
+                // No geometric solution: report why
+                None => self.solve_ik_branch(target, false, false),
+            };
+        };
+
+        let (max_step_joint, max_step_rad) = max_joint_step(prev, q);
+        IKResult {
+            reachable: true,
+            q,
+            message: "OK".to_string(),
+            continuity: Some(IKContinuity {
+                max_step_rad,
+                max_step_joint,
+                branch_switched,
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Move `target` to the nearest position `solve_ik_preferred` accepts. Reachable targets
+    /// come back unchanged; otherwise the target is clamped to the floor and the reach shell,
+    /// and a least-squares search inside the joint limits finds the closest pose where limits
+    /// cut into the shell. Projected points keep `PROJECTION_CLEARANCE_M` off the boundary so
+    /// they stay reachable after rounding. `None` if no candidate passes the IK check.
+    pub fn project_to_workspace(&self, target: Vec3) -> Option<WorkspaceProjection> {
+        let ik = self.solve_ik_preferred(target, false);
+        if ik.reachable {
+            return Some(WorkspaceProjection {
+                point: target,
+                q: ik.q,
+                error_m: 0.0,
+            });
+        }
+
+        // Floor rule first, then the reach shell around the base (kept just inside)
+        let floor = Vec3 {
+            z: target.z.max(0.0),
+            ..target
+        };
+        let p = self.base.inverse().transform_point(floor);
+        let r = p.length();
+        let margin = 1e-3;
+        let rc = r.clamp(self.min_reach() + margin, self.max_reach() - margin);
+        let on_shell = if r > 1e-9 { p * (rc / r) } else { Vec3::new(0.0, 0.0, rc) };
+        let shell = self.base.transform_point(on_shell);
+
+        let mut candidates = vec![shell];
+
+        // Where joint limits cut into the shell, descend on the distance to the target
+        // from every branch of the shell point; DLS keeps the joints inside their limits
+        let params = DlsParams {
+            max_iterations: 200,
+            ..Default::default()
+        };
+        let seeds = self
+            .solve_ik_all(shell, JointAngles::default())
+            .into_iter()
+            .map(|s| s.q)
+            .chain([JointAngles::default()]);
+        for seed in seeds {
+            let q = self.solve_ik_numeric(floor, seed, &params).q;
+            candidates.push(self.forward_kinematics(q).tcp);
+        }
+
+        let dist = |a: Vec3| a.distance(target);
+
+        let best = candidates
+            .into_iter()
+            .filter(|&c| self.solve_ik_preferred(c, false).reachable)
+            .min_by(|&a, &b| dist(a).total_cmp(&dist(b)))?;
+
+        let point = self.back_off_boundary(best);
+        let ik = self.solve_ik_preferred(point, false);
+        ik.reachable.then(|| WorkspaceProjection {
+            point,
+            q: ik.q,
+            error_m: dist(point),
+        })
+    }
+
+    /// Step a reachable boundary point inwards until its axis neighbours at
+    /// `PROJECTION_CLEARANCE_M` are reachable too (floor, shell and limit surfaces alike).
+    fn back_off_boundary(&self, p: Vec3) -> Vec3 {
+        let m = PROJECTION_CLEARANCE_M;
+        let dirs = [Vec3::X, -Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z];
+
+        let mut p = p;
+        for _ in 0..10 {
+            // Sum of directions towards unreachable neighbours points out of the workspace
+            let mut out = Vec3::ZERO;
+            for d in dirs {
+                if !self.solve_ik_preferred(p + d * m, false).reachable {
+                    out += d;
+                }
+            }
+
+            let Some(out) = out.normalized() else {
+                break;
+            };
+            let next = p - out * m;
+            if !self.solve_ik_preferred(next, false).reachable {
+                break;
+            }
+            p = next;
+        }
+        p
+    }
+
+    /// Iterative damped-least-squares IK on `serial_chain()`, starting from `seed`.
+    /// Intended to agree with `solve_ik` so both solvers can be cross-checked.
+    ///
+    /// The result depends on the seed: DLS descends to the solution nearest it and may
+    /// stall against a joint limit on the way (from a zero seed the default START
+    /// (1, 2, 1) ends 0.84 m off, pinned at the shoulder minimum). Seed with the last
+    /// command or a nearby closed-form branch.
+    pub fn solve_ik_numeric(&self, target: Vec3, seed: JointAngles, params: &DlsParams) -> IKResult {
+        let mut out = IKResult::default();
+
+        if target.z < 0.0 {
+            out.message = "Invalid target: z must be >= 0".to_string();
+            return out;
+        }
+
+        let chain = self.serial_chain();
+        let seed = [seed.q0_yaw, seed.q1_pitch, seed.q2_pitch];
+        let sol = solve_dls(&chain, target, None, &seed, params);
+
+        out.q = JointAngles {
+            q0_yaw: sol.q[0],
+            q1_pitch: sol.q[1],
+            q2_pitch: sol.q[2],
+        };
+        out.diagnostics = Some(sol.diagnostics);
+
+        if sol.diagnostics.converged {
+            out.reachable = true;
+            out.message = "OK".to_string();
+        } else {
+            out.message = format!(
+                "DLS did not converge: residual {:.3e} after {} iterations",
+                sol.diagnostics.residual, sol.diagnostics.iterations
+            );
+        }
+        out
+    }
+
+    /// First position limit broken by `q`, if any.
+    pub fn check_limits(&self, q: JointAngles) -> Option<LimitViolation> {
+        let values = [q.q0_yaw, q.q1_pitch, q.q2_pitch];
+        for (joint, (lim, value)) in self.joint_limits().iter().zip(values).enumerate() {
+            if !lim.contains(value) {
+                return Some(LimitViolation {
+                    joint,
+                    value,
+                    min: lim.min_rad,
+                    max: lim.max_rad,
+                });
+            }
+        }
+        None
+    }
+
+    /// Side of the elbow relative to the shoulder-TCP line: +1, -1, or 0 when stretched,
+    /// folded or with the TCP on the yaw axis. The effective elbow angle (link 1 to TCP)
+    /// changes sign on the reach-over-the-back branch, so it is signed by the TCP radius
+    /// in the yawed arm plane: a shoulder flip keeps the elbow on the same side.
+    fn elbow_side(&self, q: JointAngles) -> i8 {
+        let (l2, phi, _) = self.tool_link();
+        let e = wrap_angle(q.q2_pitch + phi);
+        let rho = self.l1() * q.q1_pitch.cos() + l2 * (q.q1_pitch + q.q2_pitch + phi).cos();
+        if e.abs() < 1e-4 || std::f64::consts::PI - e.abs() < 1e-4 || rho.abs() < 1e-6 {
+            0
+        } else if (e > 0.0) == (rho > 0.0) {
+            1
+        } else {
+            -1
+        }
+    }
+
+    fn solve_ik_branch(&self, target: Vec3, elbow_up: bool, shoulder_flip: bool) -> IKResult {
         let mut out = IKResult::default();
 
+        // Floor rule is in world coordinates, the rest is solved in the base frame
         if target.z < 0.0 {
             out.reachable = false;
             out.message = "Invalid target: z must be >= 0".to_string();
             return out;
         }
 
-        let x = target.x as f64;
-        let y = target.y as f64;
-        let z = target.z as f64;
+        let Vec3 { x, y, z } = self.base.inverse().transform_point(target);
 
         let d = (x * x + y * y + z * z).sqrt();
-        let rmin = self.min_reach() as f64;
-        let rmax = self.max_reach() as f64;
+        let rmin = self.min_reach();
+        let rmax = self.max_reach();
 
         if d < rmin - 1e-9 || d > rmax + 1e-9 {
             out.reachable = false;
@@ -103,24 +761,36 @@ impl RobotArm {
             return out;
         }
 
+        let l1 = self.l1();
+        let (l2, phi, tz) = self.tool_link();
+
+        // Planar radius along the arm direction u; the TCP sits tz off the arm plane
+        let rxy2 = x * x + y * y;
+        if rxy2 < tz * tz - 1e-12 {
+            out.reachable = false;
+            out.message = format!("Target is within {:.3} m of the base axis (TCP side offset)", tz.abs());
+            return out;
+        }
+        let mut r = (rxy2 - tz * tz).max(0.0).sqrt();
+        if shoulder_flip {
+            r = -r;
+        }
+
         // Base yaw in XY plane
         let mut q0 = 0.0_f64;
         if x.abs() > 1e-12 || y.abs() > 1e-12 {
-            q0 = y.atan2(x);
+            q0 = wrap_angle(y.atan2(x) - (-tz).atan2(r));
+        } else if shoulder_flip {
+            q0 = std::f64::consts::PI;
         }
 
-        // Reduce to planar (r,z) where r = sqrt(x^2+y^2)
-        let r = (x * x + y * y).sqrt();
-        let l1 = self.l1() as f64;
-        let l2 = self.l2() as f64;
-
         // Law of cosines for elbow angle
-        let mut c2 = (r * r + z * z - l1 * l1 - l2 * l2) / (2.0 * l1 * l2);
-        if c2 < -1.0 { c2 = -1.0; }
-        if c2 >  1.0 { c2 =  1.0; }
+        let c2 = ((r * r + z * z - l1 * l1 - l2 * l2) / (2.0 * l1 * l2)).clamp(-1.0, 1.0);
 
+        // Reaching over the back mirrors the plane, so the same elbow placement
+        // needs the opposite sign
         let mut q2 = c2.acos();
-        if !elbow_up {
+        if elbow_up == shoulder_flip {
             q2 = -q2;
         }
 
@@ -132,43 +802,414 @@ impl RobotArm {
         let q1 = z.atan2(r) - k2.atan2(k1);
 
         out.reachable = true;
-        out.q.q0_yaw = q0 as f32;
-        out.q.q1_pitch = q1 as f32;
-        out.q.q2_pitch = q2 as f32;
+        out.q.q0_yaw = q0;
+        out.q.q1_pitch = q1;
+        out.q.q2_pitch = q2 - phi;
         out.message = "OK".to_string();
         out
     }
 
-    pub fn forward_kinematics(&self, q: JointAngles) -> FKResult {
-        let mut fk = FKResult::default();
-        fk.base = Vector3::zero();
-        fk.joint1 = fk.base;
+    /// Every IK branch for `target` (elbow up/down x shoulder flip), nearest to `seed` first.
+    /// Empty when the target is unreachable.
+    pub fn solve_ik_all(&self, target: Vec3, seed: JointAngles) -> Vec<IKSolution> {
+        let mut out: Vec<IKSolution> = Vec::new();
 
+        for shoulder_flip in [false, true] {
+            for elbow_up in [false, true] {
+                let ik = self.solve_ik_branch(target, elbow_up, shoulder_flip);
+                if !ik.reachable {
+                    continue;
+                }
+
+                // Stretched/folded elbow: up and down coincide
+                let q = ik.q;
+                if out.iter().any(|s| joint_distance(s.q, q) < 1e-5) {
+                    continue;
+                }
+                out.push(IKSolution {
+                    q,
+                    elbow_up,
+                    shoulder_flip,
+                    distance: joint_distance(q, seed),
+                    within_limits: self.check_limits(q).is_none(),
+                });
+            }
+        }
+
+        out.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        out
+    }
+
+    pub fn forward_kinematics(&self, q: JointAngles) -> FKResult {
         let l1 = self.l1();
         let l2 = self.l2();
 
-        let cy = q.q0_yaw.cos();
-        let sy = q.q0_yaw.sin();
-        let u = Vector3 { x: cy, y: sy, z: 0.0 };
-        let k = Vector3 { x: 0.0, y: 0.0, z: 1.0 };
+        // Same DH rows as `serial_chain()`, composed without allocating
+        let f0 = self.base;
+        let f1 = f0 * Transform::dh(0.0, std::f64::consts::FRAC_PI_2, 0.0, q.q0_yaw);
+        let f2 = f1 * Transform::dh(l1, 0.0, 0.0, q.q1_pitch);
+        let f3 = f2 * Transform::dh(l2, 0.0, 0.0, q.q2_pitch);
+        let tool = f3 * self.tcp;
+
+        FKResult {
+            base: f0.translation,
+            joint1: f1.translation,
+            joint2: f2.translation,
+            ee: f3.translation,
+            tcp: tool.translation,
+            frames: [f0, f1, f2, f3],
+            tool,
+        }
+    }
+
+    pub fn singularity_proximity(&self, q: JointAngles) -> SingularityProximity {
+        let (_, phi, _) = self.tool_link();
+        let elbow = wrap_angle(q.q2_pitch + phi).abs();
+
+        // TCP in the base frame: distance from the yaw (z) axis
+        let tcp = self.forward_kinematics(q).tcp;
+        let p = self.base.inverse().transform_point(tcp);
 
-        // Elbow
-        let p1 = Vector3 {
-            x: u.x * (l1 * q.q1_pitch.cos()) + k.x * (l1 * q.q1_pitch.sin()),
-            y: u.y * (l1 * q.q1_pitch.cos()) + k.y * (l1 * q.q1_pitch.sin()),
-            z: u.z * (l1 * q.q1_pitch.cos()) + k.z * (l1 * q.q1_pitch.sin()),
+        SingularityProximity {
+            base_axis_m: p.x.hypot(p.y),
+            stretched_rad: elbow,
+            folded_rad: std::f64::consts::PI - elbow,
+        }
+    }
+
+    /// 3x3 linear-velocity Jacobian d(tcp)/dq, rows = (x, y, z), columns = (q0, q1, q2).
+    pub fn jacobian(&self, q: JointAngles) -> Mat3 {
+        let l1 = self.l1();
+        let (l2, phi, tz) = self.tool_link();
+
+        let (sy, cy) = q.q0_yaw.sin_cos();
+        let (s1, c1) = q.q1_pitch.sin_cos();
+        let (s12, c12) = (q.q1_pitch + q.q2_pitch + phi).sin_cos();
+
+        // Planar radius / height of the TCP in the yawed plane
+        let rho = l1 * c1 + l2 * c12;
+        let h = l1 * s1 + l2 * s12;
+
+        let jb = Mat3([
+            [-sy * rho + cy * tz, -cy * h, -cy * l2 * s12],
+            [ cy * rho + sy * tz, -sy * h, -sy * l2 * s12],
+            [0.0,                  rho,     l2 * c12],
+        ]);
+
+        // Base frame -> world: rotate each column
+        self.base.rotation * jb
+    }
+
+    /// Joint rates that move the TCP at `v` (J⁻¹ v); `None` at a singularity.
+    pub fn joint_rates(&self, q: JointAngles, v: Vec3) -> Option<[f64; 3]> {
+        let j = self.jacobian(q);
+        let x = solve_linear(j.0.iter().map(|row| row.to_vec()).collect(), v.to_array().to_vec())?;
+        Some([x[0], x[1], x[2]])
+    }
+
+    /// Joint accelerations that give the TCP acceleration `a` while the joints move at `qd`:
+    /// J⁻¹ (a − J̇ q̇), with J̇ q̇ from a central difference of the TCP along `qd`.
+    /// `None` at a singularity.
+    pub fn joint_accelerations(&self, q: JointAngles, qd: [f64; 3], a: Vec3) -> Option<[f64; 3]> {
+        let speed = qd.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let bias = if speed > 0.0 {
+            let h = 1e-4 / speed;
+            let q0 = q.to_array();
+            let tcp = |sign: f64| {
+                let qs: [f64; 3] = std::array::from_fn(|i| q0[i] + sign * h * qd[i]);
+                self.forward_kinematics(JointAngles::from(qs)).tcp
+            };
+            (tcp(1.0) + tcp(-1.0) - self.forward_kinematics(q).tcp * 2.0) / (h * h)
+        } else {
+            Vec3::ZERO
         };
+        self.joint_rates(q, a - bias)
+    }
+
+    pub fn jacobian_metrics(&self, q: JointAngles) -> JacobianMetrics {
+        let j = self.jacobian(q);
+        let det = j.determinant();
+
+        // Singular values from the eigenvalues of J^T J
+        let ev = sym3_eigenvalues(j.transpose() * j);
+        let smax = ev.iter().cloned().fold(0.0_f64, f64::max).max(0.0).sqrt();
+        let smin = ev.iter().cloned().fold(f64::INFINITY, f64::min).max(0.0).sqrt();
+
+        let cond = if smin > 1e-9 { smax / smin } else { f64::INFINITY };
+
+        JacobianMetrics {
+            determinant: det,
+            condition_number: cond,
+            manipulability: det.abs(),
+        }
+    }
+}
+
+/// Wrap an angle to [-pi, pi].
+fn wrap_angle(a: f64) -> f64 {
+    let two_pi = 2.0 * std::f64::consts::PI;
+    let mut w = a % two_pi;
+    if w > std::f64::consts::PI {
+        w -= two_pi;
+    } else if w < -std::f64::consts::PI {
+        w += two_pi;
+    }
+    w
+}
+
+/// Largest absolute per-joint change between two configurations, as (joint, rad).
+/// Angles are compared as-is, so a yaw jump of a full turn counts as 2*pi.
+pub fn max_joint_step(a: JointAngles, b: JointAngles) -> (usize, f64) {
+    [
+        (b.q0_yaw - a.q0_yaw).abs(),
+        (b.q1_pitch - a.q1_pitch).abs(),
+        (b.q2_pitch - a.q2_pitch).abs(),
+    ]
+    .into_iter()
+    .enumerate()
+    .fold((0, 0.0), |best, (i, d)| if d > best.1 { (i, d) } else { best })
+}
+
+fn joint_distance(a: JointAngles, b: JointAngles) -> f64 {
+    let d0 = wrap_angle(a.q0_yaw - b.q0_yaw);
+    let d1 = a.q1_pitch - b.q1_pitch;
+    let d2 = a.q2_pitch - b.q2_pitch;
+    (d0 * d0 + d1 * d1 + d2 * d2).sqrt()
+}
+
+/// Eigenvalues of a symmetric 3x3 matrix (cyclic Jacobi rotations).
+fn sym3_eigenvalues(mut a: Mat3) -> [f64; 3] {
+    for _ in 0..32 {
+        let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+        if off < 1e-24 {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q].abs() < 1e-300 {
+                continue;
+            }
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+
+            for row in a.0.iter_mut() {
+                let akp = row[p];
+                let akq = row[q];
+                row[p] = c * akp - s * akq;
+                row[q] = s * akp + c * akq;
+            }
+            let (ap, aq) = (a[p], a[q]);
+            a[p] = std::array::from_fn(|k| c * ap[k] - s * aq[k]);
+            a[q] = std::array::from_fn(|k| s * ap[k] + c * aq[k]);
+        }
+    }
+    [a[0][0], a[1][1], a[2][2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // EE
-        let a = q.q1_pitch + q.q2_pitch;
-        let p2 = Vector3 {
-            x: p1.x + u.x * (l2 * a.cos()) + k.x * (l2 * a.sin()),
-            y: p1.y + u.y * (l2 * a.cos()) + k.y * (l2 * a.sin()),
-            z: p1.z + u.z * (l2 * a.cos()) + k.z * (l2 * a.sin()),
+    fn limits(min_deg: f64, max_deg: f64) -> JointLimits {
+        JointLimits {
+            min_rad: min_deg.to_radians(),
+            max_rad: max_deg.to_radians(),
+            ..Default::default()
+        }
+    }
+
+    /// The shipped arm: 3.0 m and 2.6 m links with their pitch limits and the suction-cup TCP.
+    fn arm() -> RobotArm {
+        RobotArm::new(
+            LinkParams { length_m: 3.0, limits: limits(-20.0, 200.0), ..Default::default() },
+            LinkParams { length_m: 2.6, limits: limits(-165.0, 165.0), ..Default::default() },
+        )
+        .with_tcp(Transform::from_translation(0.34, 0.0, 0.0))
+    }
+
+    /// Largest joint difference, with yaw compared modulo a full turn.
+    fn joint_distance(a: JointAngles, b: JointAngles) -> f64 {
+        let yaw = (a.q0_yaw - b.q0_yaw).rem_euclid(std::f64::consts::TAU);
+        let yaw = yaw.min(std::f64::consts::TAU - yaw);
+        yaw.max((a.q1_pitch - b.q1_pitch).abs()).max((a.q2_pitch - b.q2_pitch).abs())
+    }
+
+    /// Reachable targets from a grid over the joint space, away from singularities.
+    fn sample_targets(arm: &RobotArm) -> Vec<Vec3> {
+        let mut targets = Vec::new();
+        for i in 0..8 {
+            for j in 0..6 {
+                for k in 0..6 {
+                    let q = JointAngles {
+                        q0_yaw: -3.0 + 0.8 * i as f64,
+                        q1_pitch: -0.2 + 0.6 * j as f64,
+                        q2_pitch: -2.6 + 1.0 * k as f64,
+                    };
+                    let p = arm.forward_kinematics(q).tcp;
+                    if p.z >= 0.0 && arm.jacobian_metrics(q).manipulability > 1.0 {
+                        targets.push(p);
+                    }
+                }
+            }
+        }
+        targets
+    }
+
+    /// TCP target `angle` rad around the base axis on a horizontal circle.
+    fn around_base(angle: f64) -> Vec3 {
+        let (s, c) = angle.sin_cos();
+        Vec3::new(3.5 * c, 3.5 * s, 1.0)
+    }
+
+    #[test]
+    fn jacobian_matches_finite_differences() {
+        // Side-offset TCP and a tilted base exercise every term
+        let arm = arm()
+            .with_tcp(Transform::from_translation(0.34, 0.1, 0.05))
+            .with_base(Transform::from_translation(0.5, -0.2, 0.3) * Transform::rot_x(0.4));
+        let h = 1e-6;
+        for q in [[0.0, 0.0, 0.0], [0.7, 1.2, -1.9], [-2.5, 0.3, 2.4], [3.0, 2.9, -0.6]] {
+            let j = arm.jacobian(q.into());
+            for i in 0..3 {
+                let (mut plus, mut minus) = (q, q);
+                plus[i] += h;
+                minus[i] -= h;
+                let column = (arm.forward_kinematics(plus.into()).tcp - arm.forward_kinematics(minus.into()).tcp) / (2.0 * h);
+                assert!(column.distance(j.column(i)) < 1e-6, "q = {q:?}, column {i}: {column:?} vs {:?}", j.column(i));
+            }
+        }
+    }
+
+    #[test]
+    fn manipulability_vanishes_at_singularities() {
+        let arm = RobotArm::new(
+            LinkParams { length_m: 3.0, ..Default::default() },
+            LinkParams { length_m: 2.6, ..Default::default() },
+        );
+        assert!(arm.jacobian_metrics([0.3, 0.8, -1.2].into()).manipulability > 1.0);
+
+        // Elbow stretched
+        let stretched = arm.jacobian_metrics([0.3, 0.8, 0.0].into());
+        assert!(stretched.manipulability < 1e-9);
+        assert!(stretched.condition_number > 1e6);
+
+        // Bent elbow with the TCP on the yaw axis: l1 cos q1 + l2 cos(q1 + q2) = 0
+        let q1: f64 = 1.2;
+        let q2 = (-arm.l1() * q1.cos() / arm.l2()).acos() - q1;
+        let q = JointAngles::from([0.3, q1, q2]);
+        assert!(arm.singularity_proximity(q).base_axis_m < 1e-9);
+        assert!(arm.singularity_proximity(q).stretched_rad > 0.5);
+        assert!(arm.jacobian_metrics(q).manipulability < 1e-9);
+    }
+
+    #[test]
+    fn numeric_ik_agrees_with_closed_form() {
+        let arm = arm();
+        let params = DlsParams::default();
+        let targets = sample_targets(&arm);
+        assert!(targets.len() > 50, "only {} sample targets", targets.len());
+
+        let mut compared = 0;
+        for target in targets {
+            for elbow_up in [true, false] {
+                let analytic = arm.solve_ik(target, elbow_up);
+                if !analytic.reachable {
+                    continue;
+                }
+                // DLS converges to the solution near its seed, so start it close to this branch
+                let q = analytic.q;
+                let seed = JointAngles {
+                    q0_yaw: q.q0_yaw + 0.15,
+                    q1_pitch: q.q1_pitch - 0.15,
+                    q2_pitch: q.q2_pitch + 0.15,
+                };
+                let numeric = arm.solve_ik_numeric(target, seed, &params);
+                assert!(numeric.reachable, "{target:?}: {}", numeric.message);
+                assert!(
+                    arm.forward_kinematics(numeric.q).tcp.distance(target) < 1e-5,
+                    "{target:?}: numeric TCP misses the target"
+                );
+                assert!(
+                    joint_distance(numeric.q, q) < 1e-4,
+                    "{target:?} elbow_up={elbow_up}: numeric {:?} vs closed form {q:?}",
+                    numeric.q
+                );
+                compared += 1;
+            }
+        }
+        assert!(compared > 50, "only {compared} solutions compared");
+    }
+
+    /// From a zero seed DLS stalls on the shoulder limit here (residual ~0.84 m).
+    #[test]
+    fn numeric_ik_reaches_default_start_from_a_nearby_seed() {
+        let arm = arm();
+        let start = Vec3::new(1.0, 2.0, 1.0);
+        let analytic = arm.solve_ik_preferred(start, false);
+        assert!(analytic.reachable);
+
+        let q = analytic.q;
+        let seed = JointAngles {
+            q0_yaw: q.q0_yaw - 0.2,
+            q1_pitch: q.q1_pitch + 0.2,
+            q2_pitch: q.q2_pitch - 0.2,
         };
+        let numeric = arm.solve_ik_numeric(start, seed, &DlsParams::default());
+        assert!(numeric.reachable, "{}", numeric.message);
+        assert!(joint_distance(numeric.q, q) < 1e-4);
+    }
 
-        fk.joint2 = p1;
-        fk.ee = p2;
-        fk
+    #[test]
+    fn shoulder_flip_keeps_the_elbow_side() {
+        let arm = arm();
+        let solutions = arm.solve_ik_all(Vec3::new(1.0, 2.0, 1.0), JointAngles::default());
+        assert_eq!(solutions.len(), 4);
+        for s in &solutions {
+            let expected = arm.elbow_side(solutions.iter().find(|o| o.elbow_up == s.elbow_up).unwrap().q);
+            assert_ne!(expected, 0);
+            assert_eq!(arm.elbow_side(s.q), expected, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn continuous_ik_unwraps_yaw_around_the_base() {
+        let arm = arm().with_cable_wrap(1.5);
+        let mut q = arm.solve_ik_preferred(around_base(0.0), false).q;
+        let start_yaw = q.q0_yaw;
+
+        // 1.25 turns in 5 degree steps
+        for i in 1..=90 {
+            let ik = arm.solve_ik_continuous(around_base((5.0 * i as f64).to_radians()), q);
+            assert!(ik.reachable, "step {i}: {}", ik.message);
+            let continuity = ik.continuity.unwrap();
+            assert!(continuity.max_step_rad < 0.1, "step {i}: {continuity:?}");
+            assert!(!continuity.branch_switched);
+            q = ik.q;
+        }
+        assert!((q.q0_yaw - start_yaw - 2.5 * std::f64::consts::PI).abs() < 1e-6, "{q:?}");
+    }
+
+    #[test]
+    fn continuous_ik_reports_the_cable_wrap_instead_of_jumping() {
+        // Half a turn either way: the wrap is hit after 180 degrees; go a quarter turn past it
+        let arm = arm().with_cable_wrap(0.5);
+        let mut q = arm.solve_ik_preferred(around_base(0.0), false).q;
+
+        let mut stopped_at = None;
+        for i in 1..=54 {
+            let ik = arm.solve_ik_continuous(around_base((5.0 * i as f64).to_radians()), q);
+            if !ik.reachable {
+                let v = ik.violation.expect("cable wrap should be reported as a limit violation");
+                assert_eq!(v.joint, 0, "{v}");
+                stopped_at.get_or_insert(i);
+                continue;
+            }
+            assert!(stopped_at.is_none(), "step {i}: solved again after the wrap by jumping to {:?}", ik.q);
+            assert!(ik.continuity.unwrap().max_step_rad < 0.1);
+            q = ik.q;
+        }
+        assert!(stopped_at.is_some());
+        assert!(q.q0_yaw <= std::f64::consts::PI);
     }
 }
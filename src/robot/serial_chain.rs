@@ -0,0 +1,268 @@
+use super::robot_arm::JointLimits;
+use super::transform::Transform;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DhConvention {
+    /// Classic DH: Rz(theta) * Tz(d) * Tx(a) * Rx(alpha)
+    Standard,
+    /// Craig's modified DH: Rx(alpha) * Tx(a) * Rz(theta) * Tz(d)
+    Modified,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JointType {
+    Revolute,
+    Prismatic,
+}
+
+/// One DH row. The joint variable is added to `theta` (revolute) or `d` (prismatic).
+#[derive(Copy, Clone, Debug)]
+pub struct DhRow {
+    pub a: f64,
+    pub alpha: f64,
+    pub d: f64,
+    pub theta: f64,
+    pub joint: JointType,
+    pub limits: JointLimits,
+}
+
+impl DhRow {
+    pub fn revolute(a: f64, alpha: f64, d: f64, theta_offset: f64) -> Self {
+        Self {
+            a,
+            alpha,
+            d,
+            theta: theta_offset,
+            joint: JointType::Revolute,
+            limits: JointLimits::default(),
+        }
+    }
+
+    pub fn prismatic(a: f64, alpha: f64, d_offset: f64, theta: f64) -> Self {
+        Self {
+            a,
+            alpha,
+            d: d_offset,
+            theta,
+            joint: JointType::Prismatic,
+            limits: JointLimits::default(),
+        }
+    }
+}
+
+/// Generic N-joint serial chain described by DH rows. Library use only: the viewer, config
+/// file and dynamics work on the 3-DOF `RobotArm`.
+#[derive(Clone, Debug)]
+pub struct SerialChain {
+    convention: DhConvention,
+    rows: Vec<DhRow>,
+    base: Transform,
+    tool: Transform,
+}
+
+impl SerialChain {
+    pub fn new(convention: DhConvention, rows: Vec<DhRow>) -> Self {
+        Self {
+            convention,
+            rows,
+            base: Transform::IDENTITY,
+            tool: Transform::IDENTITY,
+        }
+    }
+
+    /// World pose of frame 0.
+    pub fn with_base(mut self, base: Transform) -> Self {
+        self.base = base;
+        self
+    }
+
+    pub fn base(&self) -> &Transform { &self.base }
+
+    /// Tool frame relative to the last link frame.
+    pub fn with_tool(mut self, tool: Transform) -> Self {
+        self.tool = tool;
+        self
+    }
+
+    pub fn tool(&self) -> &Transform { &self.tool }
+
+    pub fn convention(&self) -> DhConvention { self.convention }
+    pub fn rows(&self) -> &[DhRow] { &self.rows }
+    pub fn dof(&self) -> usize { self.rows.len() }
+
+    /// Transform from frame `i` to frame `i + 1` for joint value `q`.
+    pub fn link_transform(&self, i: usize, q: f64) -> Transform {
+        let row = &self.rows[i];
+        let (theta, d) = match row.joint {
+            JointType::Revolute => (row.theta + q, row.d),
+            JointType::Prismatic => (row.theta, row.d + q),
+        };
+
+        match self.convention {
+            DhConvention::Standard => Transform::dh(row.a, row.alpha, d, theta),
+            DhConvention::Modified => Transform::dh_modified(row.a, row.alpha, d, theta),
+        }
+    }
+
+    /// World pose of every frame: index 0 is the base, index `dof()` the last link.
+    /// Missing joint values are treated as zero.
+    pub fn forward_kinematics(&self, q: &[f64]) -> Vec<Transform> {
+        let mut frames = Vec::with_capacity(self.rows.len() + 1);
+        let mut t = self.base;
+        frames.push(t);
+
+        for i in 0..self.rows.len() {
+            let qi = q.get(i).copied().unwrap_or(0.0);
+            t = t * self.link_transform(i, qi);
+            frames.push(t);
+        }
+        frames
+    }
+
+    /// World pose of the tool frame.
+    pub fn end_effector(&self, q: &[f64]) -> Transform {
+        self.forward_kinematics(q).pop().unwrap_or_default() * self.tool
+    }
+
+    /// Geometric Jacobian in the world frame, one column per joint:
+    /// `[vx, vy, vz, wx, wy, wz]` of the tool frame origin per unit joint rate.
+    pub fn jacobian(&self, q: &[f64]) -> Vec<[f64; 6]> {
+        let frames = self.forward_kinematics(q);
+        let pe = (frames[frames.len() - 1] * self.tool).translation;
+
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                // Standard DH rotates about z_i, modified DH about z_{i+1}
+                let joint_frame = match self.convention {
+                    DhConvention::Standard => &frames[i],
+                    DhConvention::Modified => &frames[i + 1],
+                };
+                let z = joint_frame.axis(2);
+                let p = joint_frame.translation;
+
+                match row.joint {
+                    JointType::Revolute => {
+                        let v = z.cross(pe - p);
+                        [v.x, v.y, v.z, z.x, z.y, z.z]
+                    }
+                    JointType::Prismatic => [z.x, z.y, z.z, 0.0, 0.0, 0.0],
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    use super::*;
+    use crate::robot::numeric_ik::{solve_dls, DlsParams};
+    use crate::robot::{JointAngles, LinkParams, RobotArm, Transform, Vec3};
+
+    /// PUMA 560 (standard DH, m).
+    fn puma() -> SerialChain {
+        SerialChain::new(
+            DhConvention::Standard,
+            vec![
+                DhRow::revolute(0.0, FRAC_PI_2, 0.6718, 0.0),
+                DhRow::revolute(0.4318, 0.0, 0.0, 0.0),
+                DhRow::revolute(0.0203, -FRAC_PI_2, 0.15005, 0.0),
+                DhRow::revolute(0.0, FRAC_PI_2, 0.4318, 0.0),
+                DhRow::revolute(0.0, -FRAC_PI_2, 0.0, 0.0),
+                DhRow::revolute(0.0, 0.0, 0.0, 0.0),
+            ],
+        )
+    }
+
+    /// SCARA: two horizontal links, a vertical prismatic quill and a wrist roll.
+    fn scara() -> SerialChain {
+        SerialChain::new(
+            DhConvention::Standard,
+            vec![
+                DhRow::revolute(0.35, 0.0, 0.4, 0.0),
+                DhRow::revolute(0.30, PI, 0.0, 0.0),
+                DhRow::prismatic(0.0, 0.0, 0.0, 0.0),
+                DhRow::revolute(0.0, 0.0, 0.1, 0.0),
+            ],
+        )
+    }
+
+    fn assert_close(a: Vec3, b: [f64; 3]) {
+        assert!(a.distance(Vec3::new(b[0], b[1], b[2])) < 1e-9, "{a:?} vs {b:?}");
+    }
+
+    #[test]
+    fn fk_returns_every_frame_of_four_and_six_axis_arms() {
+        assert_eq!(puma().forward_kinematics(&[0.3, -0.6, 0.4, 0.5, -0.7, 0.2]).len(), 7);
+
+        let scara = scara();
+        let frames = scara.forward_kinematics(&[0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(frames.len(), 5);
+        assert_close(frames[1].translation, [0.35, 0.0, 0.4]);
+        assert_close(frames[2].translation, [0.65, 0.0, 0.4]);
+        // The flipped z of frame 2 points the quill and the tool down
+        assert_close(frames[4].translation, [0.65, 0.0, 0.3]);
+
+        let ee = scara.end_effector(&[FRAC_PI_2, 0.0, 0.12, 0.8]);
+        assert_close(ee.translation, [0.0, 0.65, 0.18]);
+    }
+
+    #[test]
+    fn dh_model_of_the_arm_matches_closed_form_fk() {
+        let arm = RobotArm::new(
+            LinkParams { length_m: 3.0, ..Default::default() },
+            LinkParams { length_m: 2.6, ..Default::default() },
+        )
+        .with_tcp(Transform::from_translation(0.34, 0.1, 0.05));
+        let chain = arm.serial_chain();
+        for q in [[0.0, 0.0, 0.0], [0.7, 0.4, -0.9], [-2.5, 1.2, 2.1]] {
+            let tcp = arm
+                .forward_kinematics(JointAngles { q0_yaw: q[0], q1_pitch: q[1], q2_pitch: q[2] })
+                .tcp;
+            let dh = chain.end_effector(&q).translation;
+            assert!(dh.distance(tcp) < 1e-9, "q = {q:?}: {dh:?} vs {tcp:?}");
+        }
+    }
+
+    #[test]
+    fn jacobian_matches_finite_differences_on_four_and_six_axis_arms() {
+        let h = 1e-6;
+        for (chain, q) in [(puma(), vec![0.3, -0.6, 0.4, 0.5, -0.7, 0.2]), (scara(), vec![0.5, -1.0, 0.12, 0.8])] {
+            let jac = chain.jacobian(&q);
+            for (i, column) in jac.iter().enumerate() {
+                let (mut plus, mut minus) = (q.clone(), q.clone());
+                plus[i] += h;
+                minus[i] -= h;
+                let v = (chain.end_effector(&plus).translation - chain.end_effector(&minus).translation) / (2.0 * h);
+                let expected = [v.x, v.y, v.z];
+                for k in 0..3 {
+                    assert!((column[k] - expected[k]).abs() < 1e-6, "{}-axis, joint {i}: {column:?} vs {expected:?}", chain.dof());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dls_reaches_poses_of_four_and_six_axis_arms() {
+        let params = DlsParams {
+            max_iterations: 300,
+            ..Default::default()
+        };
+        let cases: [(SerialChain, Vec<f64>, Vec<f64>); 2] = [
+            (puma(), vec![0.3, -0.6, 0.4, 0.5, -0.7, 0.2], vec![0.1; 6]),
+            (scara(), vec![0.5, -1.0, 0.12, 0.8], vec![0.1, 0.1, 0.02, 0.1]),
+        ];
+        for (chain, q, offset) in cases {
+            let goal = chain.end_effector(&q);
+            let seed: Vec<f64> = q.iter().zip(&offset).map(|(q, o)| q + o).collect();
+            let sol = solve_dls(&chain, goal.translation, Some(goal.rotation), &seed, &params);
+            assert!(sol.diagnostics.converged, "{}-axis: {:?}", chain.dof(), sol.diagnostics);
+
+            let reached = chain.end_effector(&sol.q);
+            assert!(reached.translation.distance(goal.translation) < 1e-5);
+        }
+    }
+}
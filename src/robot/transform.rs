@@ -0,0 +1,122 @@
+use super::math::{Mat3, Vec3};
+
+/// Rigid homogeneous transform (rotation + translation), f64 throughout.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub rotation: Mat3,
+    pub translation: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        rotation: Mat3::IDENTITY,
+        translation: Vec3::ZERO,
+    };
+
+    pub fn from_translation(x: f64, y: f64, z: f64) -> Self {
+        Self {
+            translation: Vec3::new(x, y, z),
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn rot_x(a: f64) -> Self {
+        let (s, c) = a.sin_cos();
+        Self {
+            rotation: Mat3([[1.0, 0.0, 0.0], [0.0, c, -s], [0.0, s, c]]),
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn rot_y(a: f64) -> Self {
+        let (s, c) = a.sin_cos();
+        Self {
+            rotation: Mat3([[c, 0.0, s], [0.0, 1.0, 0.0], [-s, 0.0, c]]),
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn rot_z(a: f64) -> Self {
+        let (s, c) = a.sin_cos();
+        Self {
+            rotation: Mat3([[c, -s, 0.0], [s, c, 0.0], [0.0, 0.0, 1.0]]),
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Translation `xyz` after fixed-axis roll, pitch, yaw `rpy` (rad): R = Rz(y) Ry(p) Rx(r),
+    /// as in URDF `<origin>`.
+    pub fn from_xyz_rpy(xyz: [f64; 3], rpy: [f64; 3]) -> Self {
+        let [x, y, z] = xyz;
+        let [r, p, yaw] = rpy;
+        Self::from_translation(x, y, z) * Self::rot_z(yaw) * Self::rot_y(p) * Self::rot_x(r)
+    }
+
+    /// Classic DH link transform Rz(theta) * Tz(d) * Tx(a) * Rx(alpha).
+    pub fn dh(a: f64, alpha: f64, d: f64, theta: f64) -> Self {
+        Self::rot_z(theta) * Self::from_translation(a, 0.0, d) * Self::rot_x(alpha)
+    }
+
+    /// Modified (Craig) DH link transform Rx(alpha) * Tx(a) * Rz(theta) * Tz(d).
+    pub fn dh_modified(a: f64, alpha: f64, d: f64, theta: f64) -> Self {
+        Self::rot_x(alpha)
+            * Self::from_translation(a, 0.0, 0.0)
+            * Self::rot_z(theta)
+            * Self::from_translation(0.0, 0.0, d)
+    }
+
+    /// Composition `self * other` (apply `other` first, then `self`).
+    pub fn compose(&self, other: &Transform) -> Transform {
+        Transform {
+            rotation: self.rotation * other.rotation,
+            translation: self.transform_point(other.translation),
+        }
+    }
+
+    pub fn inverse(&self) -> Transform {
+        let rotation = self.rotation.transpose();
+        Transform {
+            rotation,
+            translation: -(rotation * self.translation),
+        }
+    }
+
+    pub fn rotate(&self, v: Vec3) -> Vec3 {
+        self.rotation * v
+    }
+
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        self.rotate(p) + self.translation
+    }
+
+    /// Column `i` of the rotation: the frame's x (0), y (1) or z (2) axis in the parent frame.
+    pub fn axis(&self, i: usize) -> Vec3 {
+        self.rotation.column(i)
+    }
+
+    /// Row-major 4x4 homogeneous matrix.
+    pub fn to_matrix(self) -> [[f64; 4]; 4] {
+        let m = &self.rotation;
+        let t = &self.translation;
+        [
+            [m[0][0], m[0][1], m[0][2], t.x],
+            [m[1][0], m[1][1], m[1][2], t.y],
+            [m[2][0], m[2][1], m[2][2], t.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    }
+}
+
+impl std::ops::Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, rhs: Transform) -> Transform {
+        self.compose(&rhs)
+    }
+}
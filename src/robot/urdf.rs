@@ -0,0 +1,577 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::math::{Mat3, Vec3};
+use super::robot_arm::{JointAngles, JointLimits, LinkParams, RobotArm};
+use super::transform::Transform;
+
+/// Tolerance used when checking that a URDF chain fits the yaw-pitch-pitch arm (m or rad).
+const GEOMETRY_TOL: f64 = 1e-6;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UrdfJointType {
+    Revolute,
+    /// Revolute without position limits
+    Continuous,
+    Prismatic,
+    Fixed,
+}
+
+impl UrdfJointType {
+    pub fn is_actuated(&self) -> bool {
+        *self != UrdfJointType::Fixed
+    }
+}
+
+/// Mass properties of a link, in the link frame.
+#[derive(Copy, Clone, Debug)]
+pub struct UrdfInertial {
+    /// Center of mass frame relative to the link frame
+    pub origin: Transform,
+    pub mass_kg: f64,
+    /// Inertia tensor about the center of mass, in the `origin` frame (kg m^2)
+    pub inertia: Mat3,
+}
+
+#[derive(Clone, Debug)]
+pub struct UrdfLink {
+    pub name: String,
+    pub inertial: Option<UrdfInertial>,
+}
+
+#[derive(Clone, Debug)]
+pub struct UrdfJoint {
+    pub name: String,
+    pub joint_type: UrdfJointType,
+    pub parent: String,
+    pub child: String,
+    /// Child link frame relative to the parent link frame at zero joint value
+    pub origin: Transform,
+    /// Unit joint axis in the child link frame
+    pub axis: Vec3,
+    pub limits: JointLimits,
+}
+
+impl UrdfJoint {
+    /// Parent-to-child transform at joint value `q` (rad or m).
+    pub fn transform(&self, q: f64) -> Transform {
+        let motion = match self.joint_type {
+            UrdfJointType::Revolute | UrdfJointType::Continuous => Transform {
+                rotation: axis_angle(self.axis, q),
+                ..Transform::IDENTITY
+            },
+            UrdfJointType::Prismatic => {
+                let p = self.axis * q;
+                Transform::from_translation(p.x, p.y, p.z)
+            }
+            UrdfJointType::Fixed => Transform::IDENTITY,
+        };
+        self.origin * motion
+    }
+}
+
+/// Why a URDF document could not be loaded or mapped onto the simulator.
+#[derive(Debug)]
+pub enum UrdfError {
+    Io { path: PathBuf, source: std::io::Error },
+    Xml(String),
+    /// A required element or attribute is absent or malformed
+    Malformed(String),
+    UnsupportedJoint { joint: String, joint_type: String },
+    /// The links/joints do not form a tree, or the requested chain does not exist
+    Topology(String),
+    /// The chain is valid URDF but the 3-DOF arm model cannot represent it
+    Incompatible(String),
+}
+
+impl fmt::Display for UrdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrdfError::Io { path, source } => write!(f, "cannot read {}: {source}", path.display()),
+            UrdfError::Xml(e) => write!(f, "XML error: {e}"),
+            UrdfError::Malformed(e) => write!(f, "malformed URDF: {e}"),
+            UrdfError::UnsupportedJoint { joint, joint_type } => write!(
+                f,
+                "joint '{joint}' has unsupported type '{joint_type}' (supported: revolute, continuous, prismatic, fixed)"
+            ),
+            UrdfError::Topology(e) => write!(f, "URDF tree: {e}"),
+            UrdfError::Incompatible(e) => write!(f, "URDF chain does not fit the 3-DOF arm: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for UrdfError {}
+
+/// Links and joints of a URDF robot description.
+#[derive(Clone, Debug)]
+pub struct UrdfModel {
+    pub name: String,
+    pub links: Vec<UrdfLink>,
+    pub joints: Vec<UrdfJoint>,
+}
+
+/// Joints from the root link to a tip link, in order.
+#[derive(Clone, Debug)]
+pub struct UrdfChain {
+    pub joints: Vec<UrdfJoint>,
+}
+
+impl UrdfChain {
+    /// Indices (into `joints`) of the joints that move.
+    pub fn actuated(&self) -> Vec<usize> {
+        (0..self.joints.len())
+            .filter(|&i| self.joints[i].joint_type.is_actuated())
+            .collect()
+    }
+
+    /// Frame of the child link of every joint, given one value per actuated joint.
+    pub fn forward_kinematics(&self, q: &[f64]) -> Vec<Transform> {
+        let mut qi = q.iter();
+        let mut t = Transform::IDENTITY;
+        self.joints
+            .iter()
+            .map(|j| {
+                let v = if j.joint_type.is_actuated() { *qi.next().unwrap_or(&0.0) } else { 0.0 };
+                t = t * j.transform(v);
+                t
+            })
+            .collect()
+    }
+
+    /// Tip link frame relative to the root link.
+    pub fn end_effector(&self, q: &[f64]) -> Transform {
+        self.forward_kinematics(q).last().copied().unwrap_or_default()
+    }
+}
+
+impl UrdfModel {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, UrdfError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| UrdfError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, UrdfError> {
+        let doc = roxmltree::Document::parse(text).map_err(|e| UrdfError::Xml(e.to_string()))?;
+        let robot = doc.root_element();
+        if robot.tag_name().name() != "robot" {
+            return Err(UrdfError::Malformed(format!("root element is <{}>, expected <robot>", robot.tag_name().name())));
+        }
+
+        let mut links = Vec::new();
+        let mut joints = Vec::new();
+        for node in robot.children().filter(|n| n.is_element()) {
+            match node.tag_name().name() {
+                "link" => links.push(parse_link(node)?),
+                "joint" => joints.push(parse_joint(node)?),
+                // Materials, transmissions, gazebo tags etc. carry no kinematics
+                _ => {}
+            }
+        }
+
+        let model = Self {
+            name: robot.attribute("name").unwrap_or_default().to_string(),
+            links,
+            joints,
+        };
+        model.check_tree()?;
+        Ok(model)
+    }
+
+    pub fn link(&self, name: &str) -> Option<&UrdfLink> {
+        self.links.iter().find(|l| l.name == name)
+    }
+
+    /// The link that is nobody's child.
+    pub fn root_link(&self) -> &str {
+        let children: Vec<&str> = self.joints.iter().map(|j| j.child.as_str()).collect();
+        self.links
+            .iter()
+            .map(|l| l.name.as_str())
+            .find(|l| !children.contains(l))
+            .unwrap_or_default()
+    }
+
+    /// Joints from the root to `tip`; without a tip the tree must have a single leaf.
+    pub fn chain(&self, tip: Option<&str>) -> Result<UrdfChain, UrdfError> {
+        let tip = match tip {
+            Some(t) => {
+                self.link(t)
+                    .ok_or_else(|| UrdfError::Topology(format!("tip link '{t}' does not exist")))?;
+                t.to_string()
+            }
+            None => {
+                let parents: Vec<&str> = self.joints.iter().map(|j| j.parent.as_str()).collect();
+                let leaves: Vec<&str> = self
+                    .links
+                    .iter()
+                    .map(|l| l.name.as_str())
+                    .filter(|l| !parents.contains(l))
+                    .collect();
+                match leaves.as_slice() {
+                    [leaf] => leaf.to_string(),
+                    _ => {
+                        return Err(UrdfError::Topology(format!(
+                            "the tree has {} leaf links ({}); name the tip link",
+                            leaves.len(),
+                            leaves.join(", ")
+                        )))
+                    }
+                }
+            }
+        };
+
+        let by_child: HashMap<&str, &UrdfJoint> = self.joints.iter().map(|j| (j.child.as_str(), j)).collect();
+        let mut joints = Vec::new();
+        let mut link = tip.as_str();
+        while let Some(j) = by_child.get(link) {
+            // A model built by hand may skip the checks in `parse`
+            if joints.len() == self.joints.len() {
+                return Err(UrdfError::Topology(format!("the chain to '{tip}' runs into a joint cycle")));
+            }
+            joints.push((*j).clone());
+            link = j.parent.as_str();
+        }
+        joints.reverse();
+        Ok(UrdfChain { joints })
+    }
+
+    /// Map the root-to-`tip` chain onto the yaw-pitch-pitch arm.
+    ///
+    /// The chain needs exactly three revolute/continuous joints: a yaw joint, then two
+    /// parallel pitch joints perpendicular to it, with the shoulder on the yaw axis and
+    /// both links stretched horizontally in the zero pose. The first fixed joint after
+    /// the elbow marks the tool flange (otherwise the tip is the flange); everything
+    /// beyond it becomes the TCP transform. Link masses and inertias about the pitch
+    /// axes are lumped from every link each pitch joint carries.
+    pub fn to_robot_arm(&self, tip: Option<&str>) -> Result<RobotArm, UrdfError> {
+        let chain = self.chain(tip)?;
+        let act = chain.actuated();
+        if act.len() != 3 {
+            return Err(UrdfError::Incompatible(format!("expected 3 actuated joints, found {}", act.len())));
+        }
+        for &i in &act {
+            let j = &chain.joints[i];
+            if j.joint_type == UrdfJointType::Prismatic {
+                return Err(UrdfError::Incompatible(format!("joint '{}' is prismatic; the arm has only revolute joints", j.name)));
+            }
+        }
+
+        let frames = chain.forward_kinematics(&[0.0; 3]);
+        let parent_frame = |i: usize| if i == 0 { Transform::IDENTITY } else { frames[i - 1] };
+        // World pose of each actuated joint at zero: origin and axis
+        let joint_pose = |i: usize| {
+            let f = parent_frame(i) * chain.joints[i].origin;
+            (f.translation, f.rotate(chain.joints[i].axis))
+        };
+        let (p0, a0) = joint_pose(act[0]);
+        let (p1, a1) = joint_pose(act[1]);
+        let (p2, a2) = joint_pose(act[2]);
+
+        let incompatible = |m: String| Err(UrdfError::Incompatible(m));
+        if a0.dot(a1).abs() > GEOMETRY_TOL {
+            return incompatible("shoulder axis is not perpendicular to the yaw axis".to_string());
+        }
+        if a1.cross(a2).length() > GEOMETRY_TOL {
+            return incompatible("shoulder and elbow axes are not parallel".to_string());
+        }
+        if (p1 - p0).cross(a0).length() > GEOMETRY_TOL {
+            return incompatible("shoulder is not on the yaw axis".to_string());
+        }
+
+        // Base frame: origin at the shoulder, z = yaw axis, x = link 1 in the zero pose
+        let link1 = p2 - p1;
+        let l1 = link1.length();
+        let Some(x) = link1.normalized() else {
+            return incompatible("shoulder and elbow coincide".to_string());
+        };
+        if x.dot(a0).abs() > GEOMETRY_TOL || x.dot(a1).abs() > GEOMETRY_TOL {
+            return incompatible("link 1 must be horizontal and perpendicular to the pitch axes in the zero pose".to_string());
+        }
+        let y = a0.cross(x);
+        // DH pitch axes are -y of the base; other joint senses need sign conventions
+        if (a1 + y).length() > GEOMETRY_TOL || (a2 + y).length() > GEOMETRY_TOL {
+            return incompatible("pitch joints rotate the opposite way to the arm's convention (axes must be x_link1 x z_yaw)".to_string());
+        }
+        let base = Transform {
+            rotation: Mat3::from_columns(x, y, a0),
+            translation: p1,
+        };
+
+        // Flange: first fixed joint after the elbow, else the tip link
+        let elbow_child = act[2];
+        let flange_idx = (elbow_child + 1..chain.joints.len())
+            .find(|&i| chain.joints[i].joint_type == UrdfJointType::Fixed)
+            .unwrap_or(chain.joints.len() - 1);
+        let flange_world = frames[flange_idx];
+        let link2 = flange_world.translation - p2;
+        let l2 = link2.dot(x);
+        if (link2 - x * l2).length() > GEOMETRY_TOL || l2 <= 0.0 {
+            return incompatible("the flange must lie on the link 1 direction, beyond the elbow, in the zero pose".to_string());
+        }
+
+        // Flange frame in the zero pose: x along the links, y = yaw axis, z = DH pitch axis
+        let flange = Transform {
+            rotation: Mat3::from_columns(x, a0, -y),
+            translation: flange_world.translation,
+        };
+        let tip_world = frames[chain.joints.len() - 1];
+        let tcp = flange.inverse() * tip_world;
+
+        let link_params = |length_m: f64, joint: usize, (mass_kg, inertia_cm, inertia_joint)| LinkParams {
+            length_m,
+            mass_kg,
+            inertia_cm,
+            inertia_joint,
+            limits: chain.joints[act[joint]].limits,
+        };
+        let link1 = link_params(l1, 1, self.lump_links(&chain, &frames, act[1]..act[2], p1, a1));
+        let link2 = link_params(l2, 2, self.lump_links(&chain, &frames, act[2]..chain.joints.len(), p2, a2));
+
+        let arm = RobotArm::from_links(link1, link2)
+            .with_base(base)
+            .with_yaw_limits(chain.joints[act[0]].limits)
+            .with_tcp(tcp);
+
+        // The mapping must reproduce the URDF kinematics exactly
+        for q in [[0.3, 0.4, -0.7], [-1.2, 1.1, 0.9], [2.5, -0.2, 1.6]] {
+            let urdf_tip = chain.end_effector(&q).translation;
+            let arm_tip = arm
+                .forward_kinematics(JointAngles {
+                    q0_yaw: q[0],
+                    q1_pitch: q[1],
+                    q2_pitch: q[2],
+                })
+                .tcp;
+            let err = urdf_tip.distance(arm_tip);
+            if err > 1e-6 * (1.0 + arm.max_reach()) {
+                return incompatible(format!("FK mismatch of {err:.3e} m after mapping"));
+            }
+        }
+        Ok(arm)
+    }
+
+    /// (mass, inertia about the parallel axis through the center of mass, inertia about
+    /// `axis` through `pivot`) of the child links of `joints`.
+    fn lump_links(
+        &self,
+        chain: &UrdfChain,
+        frames: &[Transform],
+        joints: std::ops::Range<usize>,
+        pivot: Vec3,
+        axis: Vec3,
+    ) -> (f64, f64, f64) {
+        let mut mass = 0.0;
+        let mut moment = Vec3::ZERO;
+        let mut inertia_joint = 0.0;
+        for i in joints {
+            let Some(inertial) = self.link(&chain.joints[i].child).and_then(|l| l.inertial) else {
+                continue;
+            };
+            let com_frame = frames[i] * inertial.origin;
+            let r = com_frame.translation - pivot;
+            // Axis in the inertia frame, then parallel-axis shift to the joint
+            let a = com_frame.rotation.transpose() * axis;
+            let i_com = a.dot(inertial.inertia * a);
+            let d = r - axis * r.dot(axis);
+
+            mass += inertial.mass_kg;
+            moment += r * inertial.mass_kg;
+            inertia_joint += i_com + inertial.mass_kg * d.dot(d);
+        }
+
+        let inertia_cm = if mass > 0.0 {
+            let c = moment / mass;
+            let d = c - axis * c.dot(axis);
+            inertia_joint - mass * d.dot(d)
+        } else {
+            0.0
+        };
+        (mass, inertia_cm, inertia_joint)
+    }
+
+    fn check_tree(&self) -> Result<(), UrdfError> {
+        let mut seen_children = Vec::new();
+        for j in &self.joints {
+            for link in [&j.parent, &j.child] {
+                if self.link(link).is_none() {
+                    return Err(UrdfError::Topology(format!("joint '{}' refers to unknown link '{link}'", j.name)));
+                }
+            }
+            if seen_children.contains(&j.child.as_str()) {
+                return Err(UrdfError::Topology(format!("link '{}' has more than one parent joint", j.child)));
+            }
+            seen_children.push(j.child.as_str());
+        }
+        let roots = self.links.len() - seen_children.len();
+        if self.links.is_empty() || roots != 1 {
+            return Err(UrdfError::Topology(format!("expected one root link, found {roots}")));
+        }
+
+        // With one parent per link, a link whose parents never run out sits on a cycle
+        // (which the single root does not belong to)
+        let parent_of: HashMap<&str, &str> = self.joints.iter().map(|j| (j.child.as_str(), j.parent.as_str())).collect();
+        for l in &self.links {
+            let mut link = l.name.as_str();
+            for _ in 0..=self.joints.len() {
+                match parent_of.get(link) {
+                    Some(parent) => link = parent,
+                    None => break,
+                }
+            }
+            if parent_of.contains_key(link) {
+                return Err(UrdfError::Topology(format!("link '{}' is on a joint cycle", l.name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rotation of `angle` about unit `axis` (Rodrigues).
+fn axis_angle(axis: Vec3, angle: f64) -> Mat3 {
+    let (s, c) = angle.sin_cos();
+    let Vec3 { x, y, z } = axis;
+    let t = 1.0 - c;
+    Mat3([
+        [t * x * x + c, t * x * y - s * z, t * x * z + s * y],
+        [t * x * y + s * z, t * y * y + c, t * y * z - s * x],
+        [t * x * z - s * y, t * y * z + s * x, t * z * z + c],
+    ])
+}
+
+/// URDF `<origin xyz rpy>`: rotation is fixed-axis roll, pitch, yaw = Rz(y) Ry(p) Rx(r).
+fn parse_origin(node: Option<roxmltree::Node>) -> Result<Transform, UrdfError> {
+    let Some(node) = node else {
+        return Ok(Transform::IDENTITY);
+    };
+    let xyz = parse_triple(node, "xyz")?.unwrap_or([0.0; 3]);
+    let rpy = parse_triple(node, "rpy")?.unwrap_or([0.0; 3]);
+    Ok(Transform::from_xyz_rpy(xyz, rpy))
+}
+
+fn parse_triple(node: roxmltree::Node, attr: &str) -> Result<Option<[f64; 3]>, UrdfError> {
+    let Some(text) = node.attribute(attr) else {
+        return Ok(None);
+    };
+    let values: Vec<f64> = text
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| UrdfError::Malformed(format!("<{}> {attr}=\"{text}\" is not a number list", node.tag_name().name())))?;
+    match values.as_slice() {
+        [a, b, c] => Ok(Some([*a, *b, *c])),
+        _ => Err(UrdfError::Malformed(format!("<{}> {attr}=\"{text}\" needs 3 values", node.tag_name().name()))),
+    }
+}
+
+fn parse_number(node: roxmltree::Node, attr: &str) -> Result<Option<f64>, UrdfError> {
+    node.attribute(attr)
+        .map(|v| {
+            v.trim()
+                .parse()
+                .map_err(|_| UrdfError::Malformed(format!("<{}> {attr}=\"{v}\" is not a number", node.tag_name().name())))
+        })
+        .transpose()
+}
+
+fn child<'a, 'i>(node: roxmltree::Node<'a, 'i>, tag: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn required_attr<'a>(node: roxmltree::Node<'a, '_>, attr: &str, what: &str) -> Result<&'a str, UrdfError> {
+    node.attribute(attr)
+        .ok_or_else(|| UrdfError::Malformed(format!("{what} is missing the '{attr}' attribute")))
+}
+
+fn parse_link(node: roxmltree::Node) -> Result<UrdfLink, UrdfError> {
+    let name = required_attr(node, "name", "<link>")?.to_string();
+    let inertial = match child(node, "inertial") {
+        Some(n) => {
+            let mass_kg = child(n, "mass")
+                .map(|m| parse_number(m, "value"))
+                .transpose()?
+                .flatten()
+                .ok_or_else(|| UrdfError::Malformed(format!("link '{name}': <inertial> needs <mass value>")))?;
+
+            let mut inertia = Mat3::ZERO;
+            if let Some(i) = child(n, "inertia") {
+                let get = |a: &str| parse_number(i, a).map(|v| v.unwrap_or(0.0));
+                let (ixx, ixy, ixz) = (get("ixx")?, get("ixy")?, get("ixz")?);
+                let (iyy, iyz, izz) = (get("iyy")?, get("iyz")?, get("izz")?);
+                inertia = Mat3([[ixx, ixy, ixz], [ixy, iyy, iyz], [ixz, iyz, izz]]);
+            }
+            Some(UrdfInertial {
+                origin: parse_origin(child(n, "origin"))?,
+                mass_kg,
+                inertia,
+            })
+        }
+        None => None,
+    };
+    Ok(UrdfLink { name, inertial })
+}
+
+fn parse_joint(node: roxmltree::Node) -> Result<UrdfJoint, UrdfError> {
+    let name = required_attr(node, "name", "<joint>")?.to_string();
+    let what = format!("joint '{name}'");
+    let type_text = required_attr(node, "type", &what)?;
+    let joint_type = match type_text {
+        "revolute" => UrdfJointType::Revolute,
+        "continuous" => UrdfJointType::Continuous,
+        "prismatic" => UrdfJointType::Prismatic,
+        "fixed" => UrdfJointType::Fixed,
+        other => {
+            return Err(UrdfError::UnsupportedJoint {
+                joint: name,
+                joint_type: other.to_string(),
+            })
+        }
+    };
+
+    let link_ref = |tag: &str| {
+        child(node, tag)
+            .ok_or_else(|| UrdfError::Malformed(format!("{what} has no <{tag}>")))
+            .and_then(|n| required_attr(n, "link", &format!("{what} <{tag}>")))
+            .map(str::to_string)
+    };
+    let parent = link_ref("parent")?;
+    let child_link = link_ref("child")?;
+
+    let axis = match child(node, "axis") {
+        Some(n) => Vec3::from(parse_triple(n, "xyz")?.unwrap_or([1.0, 0.0, 0.0])),
+        // URDF default axis
+        None => Vec3::X,
+    };
+    let axis = match (joint_type, axis.normalized()) {
+        (UrdfJointType::Fixed, _) => Vec3::X,
+        (_, Some(a)) => a,
+        (_, None) => return Err(UrdfError::Malformed(format!("{what} has a zero axis"))),
+    };
+
+    let mut limits = JointLimits::default();
+    if let Some(n) = child(node, "limit") {
+        if joint_type != UrdfJointType::Continuous {
+            limits.min_rad = parse_number(n, "lower")?.unwrap_or(0.0);
+            limits.max_rad = parse_number(n, "upper")?.unwrap_or(0.0);
+        }
+        if let Some(v) = parse_number(n, "velocity")?.filter(|v| *v > 0.0) {
+            limits.max_vel_rad_s = v;
+        }
+    } else if matches!(joint_type, UrdfJointType::Revolute | UrdfJointType::Prismatic) {
+        return Err(UrdfError::Malformed(format!("{what} is {type_text} and needs a <limit>")));
+    }
+
+    Ok(UrdfJoint {
+        name,
+        joint_type,
+        parent,
+        child: child_link,
+        origin: parse_origin(child(node, "origin"))?,
+        axis,
+        limits,
+    })
+}
@@ -0,0 +1,209 @@
+use crate::robot::{JointAngles, RobotArm};
+
+use super::physics::JointState;
+
+/// How joint torques are computed from the reference and the measured state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ControllerKind {
+    /// Inverse dynamics of the reference only, no feedback
+    Feedforward,
+    /// Independent joint PID plus inverse-dynamics feedforward of the reference
+    Pid,
+    /// Feedback linearisation: M(q) (q̈_ref + Kd ė + Kp e) + C(q, q̇) q̇ + g(q)
+    ComputedTorque,
+}
+
+impl ControllerKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControllerKind::Feedforward => "feedforward only",
+            ControllerKind::Pid => "PID + feedforward",
+            ControllerKind::ComputedTorque => "computed torque",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ControllerKind::Feedforward => ControllerKind::Pid,
+            ControllerKind::Pid => ControllerKind::ComputedTorque,
+            ControllerKind::ComputedTorque => ControllerKind::Feedforward,
+        }
+    }
+}
+
+/// Per-joint PID gains on the position error, in torque units (N m/rad, N m/(rad s), N m s/rad).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct PidGains {
+    pub kp: [f64; 3],
+    pub ki: [f64; 3],
+    pub kd: [f64; 3],
+    /// Anti-windup bound on the error integral (rad s)
+    pub integral_limit: [f64; 3],
+}
+
+impl Default for PidGains {
+    fn default() -> Self {
+        // About 10 rad/s closed-loop bandwidth on the default arm's stretched-out inertia
+        Self {
+            kp: [3600.0, 3600.0, 360.0],
+            ki: [2000.0, 2000.0, 200.0],
+            kd: [720.0, 720.0, 72.0],
+            integral_limit: [0.05; 3],
+        }
+    }
+}
+
+/// Computed-torque gains on the linearised error dynamics ë + Kd ė + Kp e = 0 (1/s², 1/s).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct ComputedTorqueGains {
+    pub kp: [f64; 3],
+    pub kd: [f64; 3],
+}
+
+impl Default for ComputedTorqueGains {
+    fn default() -> Self {
+        // Critically damped at 10 rad/s
+        Self {
+            kp: [100.0; 3],
+            kd: [20.0; 3],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct ControllerParams {
+    pub kind: ControllerKind,
+    pub pid: PidGains,
+    pub computed_torque: ComputedTorqueGains,
+    /// Include the arm's payload in the controller's dynamics model; when off the
+    /// controller assumes an empty tool and the load shows up as tracking error
+    pub payload_compensation: bool,
+}
+
+impl Default for ControllerParams {
+    fn default() -> Self {
+        Self {
+            kind: ControllerKind::ComputedTorque,
+            pid: PidGains::default(),
+            computed_torque: ComputedTorqueGains::default(),
+            payload_compensation: true,
+        }
+    }
+}
+
+/// Desired joint motion, e.g. from trajectory + IK.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JointReference {
+    pub q: JointAngles,
+    pub qd: [f64; 3],
+    pub qdd: [f64; 3],
+}
+
+impl JointReference {
+    /// The reference `t` seconds later (or earlier, for negative `t`), moving at constant
+    /// acceleration `qdd`.
+    pub fn at(&self, t: f64) -> Self {
+        let q = self.q.to_array();
+        Self {
+            q: JointAngles::from(std::array::from_fn(|i| q[i] + self.qd[i] * t + 0.5 * self.qdd[i] * t * t)),
+            qd: std::array::from_fn(|i| self.qd[i] + self.qdd[i] * t),
+            qdd: self.qdd,
+        }
+    }
+}
+
+/// Joint torque controller tracking a `JointReference`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JointController {
+    pub params: ControllerParams,
+    integral: [f64; 3],
+    error: [f64; 3],
+    peak_error: [f64; 3],
+}
+
+impl JointController {
+    pub fn new(params: ControllerParams) -> Self {
+        Self {
+            params,
+            ..Default::default()
+        }
+    }
+
+    /// Clear the integral and error history (on a new run or controller switch).
+    pub fn reset(&mut self) {
+        self.integral = [0.0; 3];
+        self.error = [0.0; 3];
+        self.peak_error = [0.0; 3];
+    }
+
+    /// Position error `q_ref - q` at the last call (rad).
+    pub fn tracking_error(&self) -> [f64; 3] {
+        self.error
+    }
+
+    /// Largest absolute position error since the last reset (rad).
+    pub fn peak_error(&self) -> [f64; 3] {
+        self.peak_error
+    }
+
+    /// Joint torques for one control period of `dt` seconds. `arm` is the plant,
+    /// including any payload it carries.
+    pub fn torque(&mut self, arm: &RobotArm, reference: &JointReference, state: &JointState, dt: f64) -> [f64; 3] {
+        let q_ref = reference.q.to_array();
+        let q = state.q.to_array();
+        let e: [f64; 3] = std::array::from_fn(|i| q_ref[i] - q[i]);
+        let e_dot: [f64; 3] = std::array::from_fn(|i| reference.qd[i] - state.qd[i]);
+
+        self.error = e;
+        for (peak, e) in self.peak_error.iter_mut().zip(e) {
+            *peak = peak.max(e.abs());
+        }
+
+        let unloaded;
+        let arm = if self.params.payload_compensation || arm.payload().is_none() {
+            arm
+        } else {
+            let mut model = arm.clone();
+            model.set_payload(None);
+            unloaded = model;
+            &unloaded
+        };
+
+        match self.params.kind {
+            ControllerKind::Feedforward => arm.inverse_dynamics(reference.q, reference.qd, reference.qdd),
+            ControllerKind::Pid => {
+                let g = &self.params.pid;
+                for ((acc, e), lim) in self.integral.iter_mut().zip(e).zip(g.integral_limit) {
+                    *acc = (*acc + e * dt).clamp(-lim, lim);
+                }
+                let ff = arm.inverse_dynamics(reference.q, reference.qd, reference.qdd);
+                std::array::from_fn(|i| ff[i] + g.kp[i] * e[i] + g.ki[i] * self.integral[i] + g.kd[i] * e_dot[i])
+            }
+            ControllerKind::ComputedTorque => {
+                let g = &self.params.computed_torque;
+                let qdd: [f64; 3] = std::array::from_fn(|i| reference.qdd[i] + g.kd[i] * e_dot[i] + g.kp[i] * e[i]);
+                arm.inverse_dynamics(state.q, state.qd, qdd)
+            }
+        }
+    }
+}
@@ -1,3 +1,11 @@
+pub mod control;
+pub mod physics;
+pub mod resolved_rate;
+pub mod singularity;
 pub mod trajectory;
 
-pub use trajectory::LinearTrajectory;
+pub use control::{ComputedTorqueGains, ControllerKind, ControllerParams, JointController, JointReference, PidGains};
+pub use physics::{Integrator, JointState, PhysicsParams, PhysicsSim};
+pub use resolved_rate::{ResolvedRateController, ResolvedRateParams};
+pub use singularity::{plan_segment, scan_segment, slowdown_factor, SegmentHazard, SegmentPlan, SingularityStrategy};
+pub use trajectory::{LinearTrajectory, PathSample, SpeedProfile, MAX_TIME_STRETCH};
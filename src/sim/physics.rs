@@ -0,0 +1,434 @@
+use crate::robot::numeric_ik::solve_linear;
+use crate::robot::{ActuatorParams, JointAngles, Mat3, RobotArm};
+
+/// Numerical scheme used for one physics step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Integrator {
+    /// Velocity first, then position with the new velocity (symplectic, one dynamics call)
+    SemiImplicitEuler,
+    /// Classic 4th-order Runge–Kutta with the torque held over the step
+    Rk4,
+}
+
+impl Integrator {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Integrator::SemiImplicitEuler => "semi-implicit Euler",
+            Integrator::Rk4 => "RK4",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Integrator::SemiImplicitEuler => Integrator::Rk4,
+            Integrator::Rk4 => Integrator::SemiImplicitEuler,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JointState {
+    pub q: JointAngles,
+    /// Joint rates (rad/s)
+    pub qd: [f64; 3],
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PhysicsParams {
+    /// Fixed physics step rate, independent of the render frame rate (Hz)
+    pub rate_hz: f64,
+    pub integrator: Integrator,
+    /// Most steps taken per `advance`; frame time beyond that is dropped, so a slow
+    /// frame makes the simulation lag instead of spiralling
+    pub max_steps_per_advance: usize,
+}
+
+impl Default for PhysicsParams {
+    fn default() -> Self {
+        Self {
+            rate_hz: 1000.0,
+            integrator: Integrator::Rk4,
+            max_steps_per_advance: 250,
+        }
+    }
+}
+
+/// Forward-dynamics simulation of the arm: joint torques in, joint motion out.
+/// Each joint is driven through its `ActuatorParams`: the torque saturates at the motor
+/// limit, friction opposes the drive and can hold a resting joint, and the motor crosses
+/// the backlash on its own before the gears engage again. Joints stop dead at their
+/// position limits.
+#[derive(Copy, Clone, Debug)]
+pub struct PhysicsSim {
+    pub params: PhysicsParams,
+    state: JointState,
+    drives: [Drive; 3],
+    /// Torque applied in the last step, after saturation
+    applied: [f64; 3],
+    saturated: bool,
+    /// Frame time not yet consumed by whole physics steps
+    accumulator: f64,
+    time_s: f64,
+}
+
+/// Motor side of one drive in joint units: its position relative to the link within
+/// ±backlash/2, and its rate.
+#[derive(Copy, Clone, Debug, Default)]
+struct Drive {
+    gap: f64,
+    rate: f64,
+}
+
+/// How a drive behaves over one step; decided at the start of the step.
+#[derive(Copy, Clone, Debug)]
+struct DriveMode {
+    /// Gears in contact: motor and link move together
+    engaged: bool,
+    /// Held at rest by static friction
+    stuck: bool,
+    /// Sign of the Coulomb friction while sliding
+    slip: f64,
+}
+
+/// Link and drive coordinates together, for the integrators.
+#[derive(Copy, Clone)]
+struct SimState {
+    q: [f64; 3],
+    qd: [f64; 3],
+    gap: [f64; 3],
+    rate: [f64; 3],
+}
+
+impl SimState {
+    fn offset(&self, d: &SimState, t: f64) -> SimState {
+        let add = |x: [f64; 3], dx: [f64; 3]| -> [f64; 3] { std::array::from_fn(|i| x[i] + dx[i] * t) };
+        SimState {
+            q: add(self.q, d.q),
+            qd: add(self.qd, d.qd),
+            gap: add(self.gap, d.gap),
+            rate: add(self.rate, d.rate),
+        }
+    }
+}
+
+struct Accelerations {
+    qdd: [f64; 3],
+    rate: [f64; 3],
+    /// Torque the gears pass to each link
+    transmitted: [f64; 3],
+}
+
+impl PhysicsSim {
+    pub fn new(params: PhysicsParams, q: JointAngles) -> Self {
+        let mut sim = Self {
+            params,
+            state: JointState::default(),
+            drives: [Drive::default(); 3],
+            applied: [0.0; 3],
+            saturated: false,
+            accumulator: 0.0,
+            time_s: 0.0,
+        };
+        sim.reset(JointState { q, qd: [0.0; 3] });
+        sim
+    }
+
+    /// Restart from `state` (e.g. the current command when physics is switched on).
+    /// Motors start at the middle of their backlash, moving with their joints.
+    pub fn reset(&mut self, state: JointState) {
+        self.state = state;
+        self.drives = state.qd.map(|rate| Drive { gap: 0.0, rate });
+        self.applied = [0.0; 3];
+        self.saturated = false;
+        self.accumulator = 0.0;
+        self.time_s = 0.0;
+    }
+
+    pub fn state(&self) -> JointState {
+        self.state
+    }
+
+    /// Motor positions and rates in joint units, as a motor-side encoder would read them;
+    /// they differ from `state` by the play taken up in the backlash.
+    pub fn motor_state(&self) -> JointState {
+        let q = self.state.q.to_array();
+        JointState {
+            q: JointAngles::from(std::array::from_fn(|i| q[i] + self.drives[i].gap)),
+            qd: self.drives.map(|d| d.rate),
+        }
+    }
+
+    /// Joint torques applied in the last step, after saturation (N m).
+    pub fn applied_torque(&self) -> [f64; 3] {
+        self.applied
+    }
+
+    /// Whether the last step asked for more torque than a motor can deliver.
+    pub fn saturated(&self) -> bool {
+        self.saturated
+    }
+
+    /// Simulated time since the last reset (s).
+    pub fn time_s(&self) -> f64 {
+        self.time_s
+    }
+
+    pub fn step_size(&self) -> f64 {
+        1.0 / self.params.rate_hz.max(1.0)
+    }
+
+    /// Run as many fixed steps as fit into `dt` plus the carried-over remainder.
+    /// `torque` is evaluated once per step from the state at its start.
+    /// Returns the number of steps taken.
+    pub fn advance(&mut self, arm: &RobotArm, dt: f64, mut torque: impl FnMut(&JointState) -> [f64; 3]) -> usize {
+        let h = self.step_size();
+        self.accumulator += dt.max(0.0);
+
+        let mut steps = 0;
+        while self.accumulator >= h && steps < self.params.max_steps_per_advance {
+            let tau = torque(&self.state);
+            self.step(arm, tau);
+            self.accumulator -= h;
+            steps += 1;
+        }
+        if steps == self.params.max_steps_per_advance {
+            self.accumulator = self.accumulator.min(h);
+        }
+        steps
+    }
+
+    /// One fixed step with commanded joint torques `tau` (N m).
+    pub fn step(&mut self, arm: &RobotArm, tau: [f64; 3]) {
+        let h = self.step_size();
+        let act = arm.actuators();
+        let applied: [f64; 3] = std::array::from_fn(|i| act[i].saturate(tau[i]));
+        self.saturated = applied != tau;
+        self.applied = applied;
+
+        let x = self.sim_state();
+        let modes = drive_modes(arm, &x, applied);
+        let derivative = |x: &SimState| {
+            let acc = accelerations(arm, &modes, applied, x);
+            SimState {
+                q: x.qd,
+                qd: acc.qdd,
+                gap: std::array::from_fn(|i| x.rate[i] - x.qd[i]),
+                rate: acc.rate,
+            }
+        };
+
+        let mut next = match self.params.integrator {
+            Integrator::SemiImplicitEuler => {
+                let d = derivative(&x);
+                let qd: [f64; 3] = std::array::from_fn(|i| x.qd[i] + d.qd[i] * h);
+                let rate: [f64; 3] = std::array::from_fn(|i| x.rate[i] + d.rate[i] * h);
+                SimState {
+                    q: std::array::from_fn(|i| x.q[i] + qd[i] * h),
+                    qd,
+                    gap: std::array::from_fn(|i| x.gap[i] + (rate[i] - qd[i]) * h),
+                    rate,
+                }
+            }
+            Integrator::Rk4 => {
+                let k1 = derivative(&x);
+                let k2 = derivative(&x.offset(&k1, 0.5 * h));
+                let k3 = derivative(&x.offset(&k2, 0.5 * h));
+                let k4 = derivative(&x.offset(&k3, h));
+                x.offset(&k1, h / 6.0)
+                    .offset(&k2, h / 3.0)
+                    .offset(&k3, h / 3.0)
+                    .offset(&k4, h / 6.0)
+            }
+        };
+
+        // Coulomb friction brings a sliding drive to rest instead of reversing it
+        for (i, (a, mode)) in act.iter().zip(&modes).enumerate() {
+            if a.coulomb_nm > 0.0 && mode.slip * next.rate[i] < 0.0 {
+                next.rate[i] = 0.0;
+                if mode.engaged {
+                    next.qd[i] = 0.0;
+                }
+            }
+        }
+
+        // Motors reaching the end of their play hit the link (inelastic)
+        for (i, a) in act.iter().enumerate() {
+            let half = 0.5 * a.backlash_rad;
+            if !a.has_backlash() {
+                next.gap[i] = 0.0;
+            } else if next.gap[i].abs() >= half {
+                next.gap[i] = half.copysign(next.gap[i]);
+            }
+        }
+        for (i, a) in act.iter().enumerate() {
+            if a.has_backlash() && next.gap[i].abs() >= 0.5 * a.backlash_rad && (next.rate[i] - next.qd[i]) * next.gap[i] > 0.0 {
+                gear_impact(arm, &mut next, i);
+            }
+        }
+
+        for (i, lim) in arm.joint_limits().iter().enumerate() {
+            if next.q[i] < lim.min_rad {
+                next.q[i] = lim.min_rad;
+                next.qd[i] = next.qd[i].max(0.0);
+            } else if next.q[i] > lim.max_rad {
+                next.q[i] = lim.max_rad;
+                next.qd[i] = next.qd[i].min(0.0);
+            } else {
+                continue;
+            }
+            if in_contact(&act[i], next.gap[i]) {
+                next.rate[i] = next.qd[i];
+            }
+        }
+        for (i, a) in act.iter().enumerate() {
+            if !a.has_backlash() {
+                next.rate[i] = next.qd[i];
+            }
+        }
+
+        self.state = JointState {
+            q: JointAngles::from(next.q),
+            qd: next.qd,
+        };
+        self.drives = std::array::from_fn(|i| Drive {
+            gap: next.gap[i],
+            rate: next.rate[i],
+        });
+        self.time_s += h;
+    }
+
+    fn sim_state(&self) -> SimState {
+        SimState {
+            q: self.state.q.to_array(),
+            qd: self.state.qd,
+            gap: self.drives.map(|d| d.gap),
+            rate: self.drives.map(|d| d.rate),
+        }
+    }
+}
+
+/// Whether the motor sits against the link at one end of its play (always, without backlash).
+fn in_contact(a: &ActuatorParams, gap: f64) -> bool {
+    !a.has_backlash() || gap.abs() >= 0.5 * a.backlash_rad
+}
+
+/// Contact and stiction of each drive for a step starting at `x` with torques `tau`.
+fn drive_modes(arm: &RobotArm, x: &SimState, tau: [f64; 3]) -> [DriveMode; 3] {
+    let act = arm.actuators();
+    let mut modes: [DriveMode; 3] = std::array::from_fn(|i| {
+        let a = &act[i];
+        let separating = a.has_backlash() && (x.rate[i] - x.qd[i]) * x.gap[i] < 0.0;
+        DriveMode {
+            engaged: in_contact(a, x.gap[i]) && !separating,
+            stuck: x.rate[i] == 0.0 && a.breakaway_nm() > 0.0,
+            slip: if x.rate[i] == 0.0 { 0.0 } else { x.rate[i].signum() },
+        }
+    });
+
+    // Release gears that would have to pull and brakes that cannot hold. Each pass only
+    // drops constraints, so this settles within a few passes.
+    for _ in 0..6 {
+        let pending = modes.iter().zip(act).any(|(m, a)| m.stuck || (m.engaged && a.has_backlash()));
+        if !pending {
+            break;
+        }
+        let acc = accelerations(arm, &modes, tau, x);
+        let mut changed = false;
+        for (i, (mode, a)) in modes.iter_mut().zip(act).enumerate() {
+            if mode.engaged && a.has_backlash() && acc.transmitted[i] * x.gap[i] < 0.0 {
+                mode.engaged = false;
+                changed = true;
+                continue;
+            }
+            if mode.stuck {
+                // Torque left for friction to hold
+                let net = if mode.engaged { tau[i] - acc.transmitted[i] } else { tau[i] };
+                if net.abs() > a.breakaway_nm() {
+                    mode.stuck = false;
+                    mode.slip = net.signum();
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    modes
+}
+
+/// Link and motor accelerations at `x` with the drive modes held fixed. An engaged drive
+/// adds its reflected inertia and friction to the joint; a free one leaves the link unactuated.
+fn accelerations(arm: &RobotArm, modes: &[DriveMode; 3], tau: [f64; 3], x: &SimState) -> Accelerations {
+    let act = arm.actuators();
+    let q = JointAngles::from(x.q);
+    let m = arm.mass_matrix(q);
+    let bias = arm.inverse_dynamics(q, x.qd, [0.0; 3]);
+
+    let mut a = m;
+    let mut b = [0.0; 3];
+    for (i, mode) in modes.iter().enumerate() {
+        if !mode.engaged {
+            a[i][i] -= act[i].reflected_inertia();
+            b[i] = -bias[i];
+        } else if mode.stuck {
+            a[i] = [0.0; 3];
+            a[i][i] = 1.0;
+        } else {
+            b[i] = tau[i] - act[i].friction(x.rate[i], mode.slip) - bias[i];
+        }
+    }
+    let qdd = solve(a, b).unwrap_or([0.0; 3]);
+
+    let mut acc = Accelerations {
+        qdd,
+        rate: [0.0; 3],
+        transmitted: [0.0; 3],
+    };
+    for (i, (mode, a)) in modes.iter().zip(act).enumerate() {
+        if mode.engaged {
+            let link: f64 = (0..3).map(|j| m[i][j] * qdd[j]).sum::<f64>() - a.reflected_inertia() * qdd[i];
+            acc.transmitted[i] = link + bias[i];
+            acc.rate[i] = qdd[i];
+        } else if !mode.stuck {
+            acc.rate[i] = (tau[i] - a.friction(x.rate[i], mode.slip)) / a.reflected_inertia();
+        }
+    }
+    acc
+}
+
+/// Plastic impact of drive `i` on its link: the impulse that makes motor and joint rates
+/// equal, acting on the links and on the drives already in contact.
+fn gear_impact(arm: &RobotArm, x: &mut SimState, i: usize) {
+    let act = arm.actuators();
+    let contact: [bool; 3] = std::array::from_fn(|k| k != i && in_contact(&act[k], x.gap[k]));
+    let mut m = arm.mass_matrix(JointAngles::from(x.q));
+    for (k, a) in act.iter().enumerate() {
+        if !contact[k] {
+            m[k][k] -= a.reflected_inertia();
+        }
+    }
+    let mut unit = [0.0; 3];
+    unit[i] = 1.0;
+    let rotor = act[i].reflected_inertia();
+    if let Some(response) = solve(m, unit) {
+        let impulse = (x.rate[i] - x.qd[i]) / (response[i] + 1.0 / rotor);
+        for (k, r) in response.into_iter().enumerate() {
+            x.qd[k] += r * impulse;
+            if contact[k] {
+                x.rate[k] = x.qd[k];
+            }
+        }
+    }
+    x.rate[i] = x.qd[i];
+}
+
+fn solve(m: Mat3, b: [f64; 3]) -> Option<[f64; 3]> {
+    let x = solve_linear(m.0.iter().map(|row| row.to_vec()).collect(), b.to_vec())?;
+    Some([x[0], x[1], x[2]])
+}
@@ -0,0 +1,128 @@
+use crate::robot::{dls_step, JointAngles, RobotArm, Vec3};
+
+#[derive(Copy, Clone, Debug)]
+pub struct ResolvedRateParams {
+    /// Damping applied exactly at a singularity
+    pub max_damping: f64,
+    /// Manipulability below which damping ramps in (Nakamura's rule)
+    pub manipulability_threshold: f64,
+    /// Proportional gain on TCP position error when tracking a target (1/s)
+    pub position_gain: f64,
+}
+
+impl Default for ResolvedRateParams {
+    fn default() -> Self {
+        Self {
+            max_damping: 0.4,
+            manipulability_threshold: 1.5,
+            position_gain: 4.0,
+        }
+    }
+}
+
+/// Maps a commanded Cartesian TCP velocity to joint rates through the damped
+/// Jacobian pseudo-inverse and integrates them, like a jogging pendant.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ResolvedRateController {
+    pub params: ResolvedRateParams,
+    qdot: [f64; 3],
+    saturated: bool,
+}
+
+impl ResolvedRateController {
+    pub fn new(params: ResolvedRateParams) -> Self {
+        Self {
+            params,
+            ..Default::default()
+        }
+    }
+
+    /// Joint rates applied in the last step (rad/s).
+    pub fn qdot(&self) -> [f64; 3] {
+        self.qdot
+    }
+
+    /// True if the last step had to be scaled down to respect joint velocity or acceleration limits.
+    pub fn saturated(&self) -> bool {
+        self.saturated
+    }
+
+    pub fn reset(&mut self) {
+        self.qdot = [0.0; 3];
+        self.saturated = false;
+    }
+
+    /// Integrate one step of commanded TCP velocity `v_cmd` (m/s) from `q`.
+    pub fn step(&mut self, arm: &RobotArm, q: JointAngles, v_cmd: Vec3, dt: f64) -> JointAngles {
+        if dt <= 0.0 {
+            return q;
+        }
+
+        let jac: Vec<Vec<f64>> = arm.jacobian(q).0.iter().map(|row| row.to_vec()).collect();
+
+        // Damping ramps from 0 to max_damping as manipulability drops to 0
+        let w = arm.jacobian_metrics(q).manipulability;
+        let w0 = self.params.manipulability_threshold.max(1e-6);
+        let lambda = if w < w0 {
+            self.params.max_damping * (1.0 - w / w0)
+        } else {
+            0.0
+        };
+
+        let rates = dls_step(&jac, &v_cmd.to_array(), lambda);
+        let mut qdot = [rates[0], rates[1], rates[2]];
+
+        // Uniform scaling keeps the Cartesian direction when a joint saturates
+        let limits = arm.joint_limits();
+        let mut scale = 1.0_f64;
+        for (v, lim) in qdot.iter().zip(limits.iter()) {
+            if v.abs() > lim.max_vel_rad_s {
+                scale = scale.min(lim.max_vel_rad_s / v.abs());
+            }
+        }
+        qdot.iter_mut().for_each(|v| *v *= scale);
+
+        // Likewise for the change from the last step's rates against the acceleration limits
+        let mut acc_scale = 1.0_f64;
+        for ((v, prev), lim) in qdot.iter().zip(self.qdot).zip(limits.iter()) {
+            let max_change = lim.max_acc_rad_s2 * dt;
+            if (v - prev).abs() > max_change {
+                acc_scale = acc_scale.min(max_change / (v - prev).abs());
+            }
+        }
+        for (v, prev) in qdot.iter_mut().zip(self.qdot) {
+            *v = prev + (*v - prev) * acc_scale;
+        }
+        self.saturated = scale < 1.0 || acc_scale < 1.0;
+
+        let mut next = [
+            q.q0_yaw + qdot[0] * dt,
+            q.q1_pitch + qdot[1] * dt,
+            q.q2_pitch + qdot[2] * dt,
+        ];
+        for (v, lim) in next.iter_mut().zip(limits.iter()) {
+            *v = v.clamp(lim.min_rad, lim.max_rad);
+        }
+
+        let out = JointAngles {
+            q0_yaw: next[0],
+            q1_pitch: next[1],
+            q2_pitch: next[2],
+        };
+        self.qdot = [
+            (out.q0_yaw - q.q0_yaw) / dt,
+            (out.q1_pitch - q.q1_pitch) / dt,
+            (out.q2_pitch - q.q2_pitch) / dt,
+        ];
+        out
+    }
+
+    /// Follow `target` with feed-forward velocity `v_ff` plus proportional correction
+    /// of the current TCP error.
+    pub fn track(&mut self, arm: &RobotArm, q: JointAngles, target: Vec3, v_ff: Vec3, dt: f64) -> JointAngles {
+        let tcp = arm.forward_kinematics(q).tcp;
+        let k = self.params.position_gain;
+        let v_cmd = v_ff + (target - tcp) * k;
+        self.step(arm, q, v_cmd, dt)
+    }
+}
@@ -0,0 +1,241 @@
+use crate::robot::{JointAngles, RobotArm, SingularityKind, SingularityThresholds, Vec3};
+
+/// Slowest fraction of nominal path speed used by `SlowDown`, so motion never stalls.
+const MIN_SPEED_SCALE: f64 = 0.1;
+
+/// Spacing of the IK samples used to scan a straight segment (m).
+const SCAN_STEP_M: f64 = 0.05;
+
+/// How the trajectory runner deals with segments that pass near a singularity.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SingularityStrategy {
+    /// Keep the straight path but scale path speed down with the singularity margin
+    SlowDown,
+    /// Refuse the segment with an error
+    Stop,
+    /// Route around the base axis / folded-elbow region through a via point,
+    /// slowing down where no detour exists (e.g. a stretched arm at an endpoint)
+    Detour,
+}
+
+impl SingularityStrategy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SingularityStrategy::SlowDown => "slow down",
+            SingularityStrategy::Stop => "stop with error",
+            SingularityStrategy::Detour => "detour",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            SingularityStrategy::SlowDown => SingularityStrategy::Stop,
+            SingularityStrategy::Stop => SingularityStrategy::Detour,
+            SingularityStrategy::Detour => SingularityStrategy::SlowDown,
+        }
+    }
+}
+
+/// First near-singular point found along a segment.
+#[derive(Copy, Clone, Debug)]
+pub struct SegmentHazard {
+    /// Fraction along the segment, 0 = start
+    pub alpha: f64,
+    pub point: Vec3,
+    pub kind: SingularityKind,
+}
+
+/// How `plan_segment` runs a segment.
+#[derive(Copy, Clone, Debug)]
+pub enum SegmentPlan {
+    /// The straight path: clear of singularities, or the strategy is `SlowDown`
+    Straight,
+    /// Around the hazard through this via point
+    Detour(Vec3),
+    /// `Detour` found no clear via point, so the straight path runs slowed down past the hazard
+    SlowDownFallback(SegmentHazard),
+}
+
+impl SegmentPlan {
+    pub fn via(&self) -> Option<Vec3> {
+        match self {
+            SegmentPlan::Detour(via) => Some(*via),
+            _ => None,
+        }
+    }
+}
+
+/// Sample the straight segment `from -> to` and return the first point whose IK
+/// solution lies inside the singularity thresholds.
+pub fn scan_segment(arm: &RobotArm, from: Vec3, to: Vec3, th: &SingularityThresholds) -> Option<SegmentHazard> {
+    let len = from.distance(to);
+    let n = ((len / SCAN_STEP_M).ceil() as usize).clamp(16, 400);
+
+    (0..=n).find_map(|i| {
+        let alpha = i as f64 / n as f64;
+        let point = from.lerp(to, alpha);
+        let ik = arm.solve_ik_preferred(point, false);
+        if !ik.reachable {
+            return None;
+        }
+        arm.singularity_proximity(ik.q)
+            .nearest(th)
+            .map(|kind| SegmentHazard { alpha, point, kind })
+    })
+}
+
+/// Via point that takes `from -> via -> to` clear of `hazard`, if one exists.
+/// Base-axis hazards are pushed sideways away from the yaw axis, folded-elbow
+/// hazards radially away from the shoulder (which sits on the base origin).
+pub fn detour_via(
+    arm: &RobotArm,
+    from: Vec3,
+    to: Vec3,
+    hazard: &SegmentHazard,
+    th: &SingularityThresholds,
+) -> Option<Vec3> {
+    // Work in the base frame so the yaw axis is z
+    let inv = arm.base().inverse();
+    let a = inv.transform_point(from);
+    let d = inv.transform_point(to) - a;
+
+    let (c, dir) = match hazard.kind {
+        SingularityKind::BaseAxis => {
+            // Closest approach of the horizontal projection to the axis
+            let len2 = d.x * d.x + d.y * d.y;
+            let t = if len2 > 1e-12 {
+                (-(a.x * d.x + a.y * d.y) / len2).clamp(0.0, 1.0)
+            } else {
+                return None;
+            };
+            let c = a + d * t;
+            let r = c.x.hypot(c.y);
+            let dir = if r > 1e-6 {
+                Vec3::new(c.x / r, c.y / r, 0.0)
+            } else {
+                let l = len2.sqrt();
+                Vec3::new(-d.y / l, d.x / l, 0.0)
+            };
+            (c, dir)
+        }
+        SingularityKind::ElbowFolded => {
+            let len2 = d.dot(d);
+            if len2 < 1e-12 {
+                return None;
+            }
+            let t = (-a.dot(d) / len2).clamp(0.0, 1.0);
+            let c = a + d * t;
+            (c, c.normalized()?)
+        }
+        // The outer boundary is convex: a straight segment only gets close to it at its ends
+        SingularityKind::ElbowStretched => return None,
+    };
+
+    let step = th.base_axis_m.max(0.05);
+    let base = *arm.base();
+    (1..=8).find_map(|k| {
+        let via = base.transform_point(c + dir * (step * k as f64));
+
+        let clear = arm.solve_ik_preferred(via, false).reachable
+            && scan_segment(arm, from, via, th).is_none()
+            && scan_segment(arm, via, to, th).is_none();
+        clear.then_some(via)
+    })
+}
+
+/// Decide how to run the segment `from -> to` under `strategy`.
+/// Returns the plan, or an error message for `Stop`.
+pub fn plan_segment(
+    arm: &RobotArm,
+    from: Vec3,
+    to: Vec3,
+    strategy: SingularityStrategy,
+    th: &SingularityThresholds,
+) -> Result<SegmentPlan, String> {
+    let Some(hazard) = scan_segment(arm, from, to, th) else {
+        return Ok(SegmentPlan::Straight);
+    };
+
+    match strategy {
+        SingularityStrategy::Stop => Err(format!(
+            "Path passes near a singularity ({}) at ({:.2}, {:.2}, {:.2}).",
+            hazard.kind.label(),
+            hazard.point.x,
+            hazard.point.y,
+            hazard.point.z
+        )),
+        SingularityStrategy::Detour => Ok(match detour_via(arm, from, to, &hazard, th) {
+            Some(via) => SegmentPlan::Detour(via),
+            None => SegmentPlan::SlowDownFallback(hazard),
+        }),
+        SingularityStrategy::SlowDown => Ok(SegmentPlan::Straight),
+    }
+}
+
+/// Path speed multiplier for configuration `q`: 1 away from singularities,
+/// shrinking with the margin so joint rates stay bounded near them.
+pub fn slowdown_factor(arm: &RobotArm, q: JointAngles, th: &SingularityThresholds) -> f64 {
+    arm.singularity_proximity(q).margin(th).max(MIN_SPEED_SCALE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robot::{JointLimits, LinkParams, Transform};
+
+    fn limits(min_deg: f64, max_deg: f64) -> JointLimits {
+        JointLimits {
+            min_rad: min_deg.to_radians(),
+            max_rad: max_deg.to_radians(),
+            ..Default::default()
+        }
+    }
+
+    /// The shipped arm: 3.0 m and 2.6 m links with their pitch limits and the suction-cup TCP.
+    fn arm() -> RobotArm {
+        RobotArm::new(
+            LinkParams { length_m: 3.0, limits: limits(-20.0, 200.0), ..Default::default() },
+            LinkParams { length_m: 2.6, limits: limits(-165.0, 165.0), ..Default::default() },
+        )
+        .with_tcp(Transform::from_translation(0.34, 0.0, 0.0))
+    }
+
+    /// Straight over the top of the yaw axis.
+    const ACROSS_BASE: (Vec3, Vec3) = (Vec3::new(2.0, 0.5, 1.5), Vec3::new(-2.0, -0.5, 1.5));
+
+    #[test]
+    fn detour_around_the_base_axis_scans_clean() {
+        let (arm, th) = (arm(), SingularityThresholds::default());
+        let (from, to) = ACROSS_BASE;
+        let hazard = scan_segment(&arm, from, to, &th).unwrap();
+        assert_eq!(hazard.kind, SingularityKind::BaseAxis);
+
+        let plan = plan_segment(&arm, from, to, SingularityStrategy::Detour, &th).unwrap();
+        let SegmentPlan::Detour(via) = plan else {
+            panic!("expected a detour, got {plan:?}");
+        };
+        assert!(scan_segment(&arm, from, via, &th).is_none());
+        assert!(scan_segment(&arm, via, to, &th).is_none());
+    }
+
+    #[test]
+    fn stop_refuses_a_hazardous_segment() {
+        let (arm, th) = (arm(), SingularityThresholds::default());
+        let (from, to) = ACROSS_BASE;
+        assert!(plan_segment(&arm, from, to, SingularityStrategy::Stop, &th).is_err());
+        assert!(matches!(plan_segment(&arm, from, to, SingularityStrategy::SlowDown, &th), Ok(SegmentPlan::Straight)));
+    }
+
+    #[test]
+    fn stretched_endpoint_falls_back_to_slowing_down() {
+        let (arm, th) = (arm(), SingularityThresholds::default());
+        // Ends about 7 degrees short of the fully stretched elbow
+        let (from, to) = (Vec3::new(2.0, 2.0, 2.0), Vec3::new(5.845, 0.0, 1.0));
+        let plan = plan_segment(&arm, from, to, SingularityStrategy::Detour, &th).unwrap();
+        assert!(
+            matches!(plan, SegmentPlan::SlowDownFallback(hazard) if hazard.kind == SingularityKind::ElbowStretched),
+            "{plan:?}"
+        );
+        assert_eq!(plan.via(), None);
+    }
+}
@@ -1,58 +1,310 @@
-use raylib::prelude::*;
+use crate::robot::{JointAngles, RobotArm, Vec3};
+
+/// Most a segment is slowed down to respect joint rate and acceleration limits; paths
+/// that need more (close to a singularity) run at this stretch and are reported.
+pub const MAX_TIME_STRETCH: f64 = 10.0;
+
+/// Path samples per segment when checking joint limits.
+const LIMIT_SAMPLES: usize = 200;
+
+/// Trapezoidal TCP speed along each straight leg: ramp up at `accel_mps2` to `speed_mps`,
+/// cruise, ramp down to rest.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpeedProfile {
+    pub speed_mps: f64,
+    pub accel_mps2: f64,
+}
+
+impl Default for SpeedProfile {
+    fn default() -> Self {
+        Self {
+            speed_mps: 1.75,
+            accel_mps2: 3.5,
+        }
+    }
+}
+
+/// TCP position, velocity and acceleration at one instant of a trajectory.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PathSample {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub acceleration: Vec3,
+}
+
+/// One straight leg, timed with a trapezoidal (or, if too short to reach cruise speed,
+/// triangular) speed profile.
+#[derive(Copy, Clone, Debug)]
+struct Leg {
+    from: Vec3,
+    to: Vec3,
+    speed: f64,
+    accel: f64,
+    duration: f64,
+}
+
+impl Leg {
+    fn new(from: Vec3, to: Vec3, profile: SpeedProfile) -> Self {
+        let length = from.distance(to);
+        let accel = profile.accel_mps2.max(1e-6);
+        let speed = profile.speed_mps.max(1e-6).min((length * accel).sqrt());
+        let duration = if speed > 0.0 { length / speed + speed / accel } else { 0.0 };
+        Self {
+            from,
+            to,
+            speed,
+            accel,
+            duration,
+        }
+    }
+
+    /// The same leg run `k` times slower.
+    fn stretched(self, k: f64) -> Self {
+        Self {
+            speed: self.speed / k,
+            accel: self.accel / (k * k),
+            duration: self.duration * k,
+            ..self
+        }
+    }
+
+    fn sample(&self, t: f64) -> PathSample {
+        let dir = (self.to - self.from).normalized().unwrap_or(Vec3::ZERO);
+        let t = t.clamp(0.0, self.duration);
+        let ramp = self.speed / self.accel;
+        let (s, v, a) = if t < ramp {
+            (0.5 * self.accel * t * t, self.accel * t, self.accel)
+        } else if t > self.duration - ramp {
+            let left = self.duration - t;
+            (self.from.distance(self.to) - 0.5 * self.accel * left * left, self.accel * left, -self.accel)
+        } else {
+            (0.5 * self.speed * ramp + self.speed * (t - ramp), self.speed, 0.0)
+        };
+        PathSample {
+            position: self.from + dir * s,
+            velocity: dir * v,
+            acceleration: dir * a,
+        }
+    }
+}
 
 #[derive(Copy, Clone, Debug)]
 pub struct LinearTrajectory {
-    a: Vector3,
-    b: Vector3,
-    duration: f32,
-    t: f32,
-    alpha: f32,
+    /// Timing used by the next `reset`
+    pub profile: SpeedProfile,
+    legs: [Leg; 2],
+    /// Optional corner between start and end; the path is then two straight legs, with
+    /// the TCP coming to rest at the corner
+    via: Option<Vec3>,
+    t: f64,
     finished: bool,
 }
 
 impl Default for LinearTrajectory {
     fn default() -> Self {
+        Self::new(SpeedProfile::default())
+    }
+}
+
+impl LinearTrajectory {
+    pub fn new(profile: SpeedProfile) -> Self {
+        let rest = Leg::new(Vec3::ZERO, Vec3::ZERO, profile);
         Self {
-            a: Vector3::zero(),
-            b: Vector3::zero(),
-            duration: 1.0,
+            profile,
+            legs: [rest; 2],
+            via: None,
             t: 0.0,
-            alpha: 0.0,
             finished: true,
         }
     }
-}
 
-impl LinearTrajectory {
-    pub fn reset(&mut self, from: Vector3, to: Vector3, duration_sec: f32) {
-        self.a = from;
-        self.b = to;
-        self.duration = if duration_sec > 1e-6 { duration_sec } else { 1e-6 };
+    pub fn reset(&mut self, from: Vec3, to: Vec3) {
+        self.reset_via(from, None, to);
+    }
+
+    /// Like `reset`, but passing through `via`.
+    pub fn reset_via(&mut self, from: Vec3, via: Option<Vec3>, to: Vec3) {
+        let corner = via.unwrap_or(to);
+        self.legs = [Leg::new(from, corner, self.profile), Leg::new(corner, to, self.profile)];
+        self.via = via;
         self.t = 0.0;
-        self.alpha = 0.0;
         self.finished = false;
     }
 
-    pub fn update(&mut self, dt: f32) {
+    /// Run the whole trajectory `k` (≥ 1) times slower: joint rates scale by 1/k and
+    /// joint accelerations by 1/k².
+    pub fn stretch(&mut self, k: f64) {
+        let k = k.max(1.0);
+        self.legs = self.legs.map(|leg| leg.stretched(k));
+    }
+
+    /// Slow the trajectory down (at most `MAX_TIME_STRETCH` times) until the joint rates
+    /// and accelerations along it stay within the arm's limits, following the path by IK
+    /// from `q_start`. Returns false if even the slowest timing breaks them.
+    pub fn fit_joint_limits(&mut self, arm: &RobotArm, q_start: JointAngles) -> bool {
+        let limits = arm.joint_limits();
+        let duration = self.duration();
+        let mut q = q_start;
+        let mut k = 1.0_f64;
+        for i in 0..=LIMIT_SAMPLES {
+            let s = self.sample(duration * i as f64 / LIMIT_SAMPLES as f64);
+            let ik = arm.solve_ik_continuous(s.position, q);
+            if !ik.reachable {
+                continue;
+            }
+            q = ik.q;
+            let Some(qd) = arm.joint_rates(q, s.velocity) else { continue };
+            let Some(qdd) = arm.joint_accelerations(q, qd, s.acceleration) else { continue };
+            for ((v, a), lim) in qd.into_iter().zip(qdd).zip(&limits) {
+                k = k.max(v.abs() / lim.max_vel_rad_s).max((a.abs() / lim.max_acc_rad_s2).sqrt());
+            }
+        }
+        self.stretch(k.min(MAX_TIME_STRETCH));
+        k <= MAX_TIME_STRETCH
+    }
+
+    pub fn update(&mut self, dt: f64) {
         if self.finished {
             return;
         }
         self.t += dt;
-        self.alpha = (self.t / self.duration).clamp(0.0, 1.0);
-        if self.alpha >= 1.0 {
+        if self.t >= self.duration() {
             self.finished = true;
         }
     }
 
-    pub fn position(&self) -> Vector3 {
-        Vector3 {
-            x: self.a.x + (self.b.x - self.a.x) * self.alpha,
-            y: self.a.y + (self.b.y - self.a.y) * self.alpha,
-            z: self.a.z + (self.b.z - self.a.z) * self.alpha,
+    /// Total time of both legs (s).
+    pub fn duration(&self) -> f64 {
+        self.legs[0].duration + self.legs[1].duration
+    }
+
+    /// Position, velocity and acceleration `t` seconds after the start.
+    pub fn sample(&self, t: f64) -> PathSample {
+        let first = &self.legs[0];
+        if t < first.duration {
+            first.sample(t)
+        } else {
+            self.legs[1].sample(t - first.duration)
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.sample(self.t).position
+    }
+
+    /// Velocity of the current leg while running, zero once finished.
+    pub fn velocity(&self) -> Vec3 {
+        if self.finished {
+            return Vec3::ZERO;
         }
+        self.sample(self.t).velocity
+    }
+
+    /// Acceleration of the current leg while running, zero once finished.
+    pub fn acceleration(&self) -> Vec3 {
+        if self.finished {
+            return Vec3::ZERO;
+        }
+        self.sample(self.t).acceleration
+    }
+
+    pub fn via(&self) -> Option<Vec3> {
+        self.via
+    }
+
+    /// Total path length through the via point, if any.
+    pub fn length(&self) -> f64 {
+        self.legs.iter().map(|leg| leg.from.distance(leg.to)).sum()
     }
 
     pub fn finished(&self) -> bool {
         self.finished
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robot::{JointLimits, LinkParams, Transform};
+
+    fn limits(min_deg: f64, max_deg: f64, max_vel_rad_s: f64, max_acc_rad_s2: f64) -> JointLimits {
+        JointLimits {
+            min_rad: min_deg.to_radians(),
+            max_rad: max_deg.to_radians(),
+            max_vel_rad_s,
+            max_acc_rad_s2,
+        }
+    }
+
+    /// The shipped arm with its joint rate and acceleration limits.
+    fn arm() -> RobotArm {
+        RobotArm::new(
+            LinkParams { length_m: 3.0, limits: limits(-20.0, 200.0, 2.0, 8.0), ..Default::default() },
+            LinkParams { length_m: 2.6, limits: limits(-165.0, 165.0, 2.5, 10.0), ..Default::default() },
+        )
+        .with_yaw_limits(limits(-180.0, 180.0, 2.0, 8.0))
+        .with_tcp(Transform::from_translation(0.34, 0.0, 0.0))
+    }
+
+    /// Trajectory from `from` to `to` under `profile`, fitted to the arm's limits.
+    fn fitted(arm: &RobotArm, profile: SpeedProfile, from: Vec3, to: Vec3) -> (LinearTrajectory, f64, bool) {
+        let mut traj = LinearTrajectory::new(profile);
+        traj.reset(from, to);
+        let unfitted = traj.duration();
+        let q = arm.solve_ik_preferred(from, false).q;
+        let within_limits = traj.fit_joint_limits(arm, q);
+        (traj, unfitted, within_limits)
+    }
+
+    /// Largest joint rate and acceleration along the trajectory, relative to the limits.
+    fn peak_limit_ratio(arm: &RobotArm, traj: &LinearTrajectory, from: Vec3) -> f64 {
+        let limits = arm.joint_limits();
+        let mut q = arm.solve_ik_preferred(from, false).q;
+        let mut ratio = 0.0_f64;
+        for i in 0..=1000 {
+            let s = traj.sample(traj.duration() * i as f64 / 1000.0);
+            q = arm.solve_ik_continuous(s.position, q).q;
+            let qd = arm.joint_rates(q, s.velocity).unwrap();
+            let qdd = arm.joint_accelerations(q, qd, s.acceleration).unwrap();
+            for ((v, a), lim) in qd.into_iter().zip(qdd).zip(&limits) {
+                ratio = ratio.max(v.abs() / lim.max_vel_rad_s).max(a.abs() / lim.max_acc_rad_s2);
+            }
+        }
+        ratio
+    }
+
+    #[test]
+    fn slow_segment_keeps_its_timing() {
+        let arm = arm();
+        let (from, to) = (Vec3::new(1.0, 2.0, 1.0), Vec3::new(2.0, 2.0, 2.0));
+        let profile = SpeedProfile { speed_mps: 0.2, accel_mps2: 0.4 };
+        let (traj, unfitted, within_limits) = fitted(&arm, profile, from, to);
+        assert!(within_limits);
+        assert_eq!(traj.duration(), unfitted);
+        assert!(peak_limit_ratio(&arm, &traj, from) < 1.0);
+    }
+
+    #[test]
+    fn fast_segment_is_stretched_to_the_limits() {
+        let arm = arm();
+        let (from, to) = (Vec3::new(1.0, 2.0, 1.0), Vec3::new(-2.0, 3.0, 2.5));
+        let profile = SpeedProfile { speed_mps: 8.0, accel_mps2: 20.0 };
+        let (traj, unfitted, within_limits) = fitted(&arm, profile, from, to);
+        assert!(within_limits);
+        assert!(traj.duration() > 1.1 * unfitted, "{} vs {unfitted}", traj.duration());
+
+        // Fitted on 200 samples; allow a little overshoot between them
+        let ratio = peak_limit_ratio(&arm, &traj, from);
+        assert!(ratio > 0.9 && ratio < 1.02, "peak rate/acceleration at {ratio:.3} of the limit");
+    }
+
+    #[test]
+    fn stretch_is_capped() {
+        let arm = arm();
+        let (from, to) = (Vec3::new(1.0, 2.0, 1.0), Vec3::new(-2.0, 3.0, 2.5));
+        let profile = SpeedProfile { speed_mps: 200.0, accel_mps2: 2000.0 };
+        let (traj, unfitted, within_limits) = fitted(&arm, profile, from, to);
+        assert!(!within_limits);
+        assert!((traj.duration() - MAX_TIME_STRETCH * unfitted).abs() < 1e-9);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod overlay;
 
-pub use overlay::{draw_overlay_panel, OverlayAction, OverlayState, OverlayStatus, UiInput};
+pub use overlay::{
+    draw_overlay_panel, draw_runtime_panel, OverlayAction, OverlayState, OverlayStatus, RuntimeStatus, UiInput,
+};
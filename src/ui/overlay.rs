@@ -2,7 +2,7 @@ use raylib::core::drawing::RaylibDraw;
 use raylib::prelude::*;
 
 use crate::render;
-use crate::robot::RobotArm;
+use manipulator3d::robot::{JointAngles, RobotArm, SingularityKind, TorqueStats, Vec3, WorkspaceProjection, JOINT_NAMES};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum FocusField {
@@ -27,28 +27,91 @@ pub struct OverlayStatus<'a> {
     pub error_text: Option<&'a str>,
 }
 
+/// Live joint state shown in the right-hand panel.
+#[derive(Copy, Clone, Debug)]
+pub struct RuntimeStatus<'a> {
+    pub control_text: &'a str,
+    /// Joint angles on screen (simulated with physics on, else commanded), model convention
+    pub q: JointAngles,
+    /// The same angles as the encoders read them
+    pub q_hardware: JointAngles,
+    pub qdot: [f64; 3],
+    /// Inverse-dynamics torque of the commanded motion (N m)
+    pub torque: [f64; 3],
+    /// Peak/RMS torque over the last completed pick-and-place cycle, or over the current
+    /// one until the first completes
+    pub torque_stats: TorqueStats,
+    pub torque_stats_complete: bool,
+    /// (joint, rad) of the largest joint change in the last tick
+    pub max_step: (usize, f64),
+    /// IK moved the elbow to the other side to stay within limits
+    pub branch_switched: bool,
+    /// Resolved-rate step was scaled down by a joint velocity or acceleration limit
+    pub rate_limited: bool,
+    /// The current segment could not be slowed down enough for the joint speed/acceleration limits
+    pub path_over_limits: bool,
+    pub strategy_text: &'a str,
+    pub physics_text: &'a str,
+    pub controller_text: &'a str,
+    pub payload_text: &'a str,
+    /// (now, peak) joint tracking error `q_ref - q` while physics runs (rad)
+    pub tracking_error: Option<([f64; 3], [f64; 3])>,
+    pub workspace_text: &'a str,
+    /// Result of the last file export (empty if none)
+    pub export_note: &'a str,
+    /// Closest singularity within the warning thresholds
+    pub singularity: Option<SingularityKind>,
+}
+
 #[derive(Debug)]
 pub enum OverlayAction {
     None,
     Paused(bool),
-    StartSimulation { start: Vector3, goal: Vector3 },
+    StartSimulation { start: Vec3, goal: Vec3 },
+}
+
+/// Nearest reachable point for an input field, kept until the field's text changes
+/// (the projection searches the workspace, too slow to redo every frame).
+#[derive(Debug, Default)]
+struct SnapCache {
+    text: String,
+    projection: Option<WorkspaceProjection>,
+}
+
+impl SnapCache {
+    fn get(&mut self, arm: &RobotArm, text: &str, target: Vec3) -> Option<WorkspaceProjection> {
+        if self.text != text {
+            self.text = text.to_string();
+            self.projection = arm.project_to_workspace(target);
+        }
+        self.projection
+    }
 }
 
 #[derive(Debug)]
 pub struct OverlayState {
+    home: Vec3,
     start_text: String,
     goal_text: String,
     focus: FocusField,
     local_error: Option<String>,
+    /// Confirmation of the last snap-to-workspace
+    local_note: Option<String>,
+    start_snap: SnapCache,
+    goal_snap: SnapCache,
 }
 
 impl OverlayState {
-    pub fn new(start: &str, goal: &str) -> Self {
+    pub fn new(home: Vec3, start: Vec3, goal: Vec3) -> Self {
         Self {
-            start_text: start.to_string(),
-            goal_text: goal.to_string(),
+            home,
+            start_text: format!("{} {} {}", start.x, start.y, start.z),
+            goal_text: format!("{} {} {}", goal.x, goal.y, goal.z),
             focus: FocusField::Start,
             local_error: None,
+            local_note: None,
+            start_snap: SnapCache::default(),
+            goal_snap: SnapCache::default(),
         }
     }
 }
@@ -77,16 +140,16 @@ fn backspace(buf: &mut String) {
     buf.pop();
 }
 
-fn try_parse_vec3(s: &str) -> Option<Vector3> {
-    let mut it = s.split_whitespace();
-    let x: f32 = it.next()?.parse().ok()?;
-    let y: f32 = it.next()?.parse().ok()?;
-    let z: f32 = it.next()?.parse().ok()?;
-    Some(Vector3 { x, y, z })
+fn format_vec3(p: Vec3) -> String {
+    format!("{:.4} {:.4} {:.4}", p.x, p.y, p.z)
 }
 
-fn norm3(v: Vector3) -> f32 {
-    (v.x * v.x + v.y * v.y + v.z * v.z).sqrt()
+fn try_parse_vec3(s: &str) -> Option<Vec3> {
+    let mut it = s.split_whitespace();
+    let x: f64 = it.next()?.parse().ok()?;
+    let y: f64 = it.next()?.parse().ok()?;
+    let z: f64 = it.next()?.parse().ok()?;
+    Some(Vec3 { x, y, z })
 }
 
 pub fn draw_overlay_panel<D, F>(
@@ -163,6 +226,7 @@ where
 
     if input.key_escape && paused {
         overlay.local_error = None;
+        overlay.local_note = None;
     }
 
     if paused {
@@ -248,7 +312,7 @@ where
     render::draw_text_small(
         d,
         font,
-        format!("Workspace |p|: [{:.2}, {:.2}] m", rmin, rmax).as_str(),
+        format!("Workspace |p-base|: [{:.2}, {:.2}] m", rmin, rmax).as_str(),
         x0 + pad,
         y,
         18.0,
@@ -256,26 +320,39 @@ where
     );
     y += 24;
 
-    let home = Vector3 { x: 2.0, y: 2.0, z: 2.0 };
-    let home_ok = arm.solve_ik(home, false).reachable;
+    let home = overlay.home;
+    let home_ok = arm.solve_ik_preferred(home, false).reachable;
 
     let parsed_start = try_parse_vec3(&overlay.start_text);
     let parsed_goal = try_parse_vec3(&overlay.goal_text);
 
     let mut start_ok = false;
     let mut goal_ok = false;
-    let mut ds = 0.0_f32;
-    let mut dg = 0.0_f32;
+    let mut ds = 0.0_f64;
+    let mut dg = 0.0_f64;
 
     if let Some(s) = parsed_start {
-        start_ok = arm.solve_ik(s, false).reachable;
-        ds = norm3(s);
+        start_ok = arm.solve_ik_preferred(s, false).reachable;
+        ds = arm.distance_from_base(s);
     }
     if let Some(g) = parsed_goal {
-        goal_ok = arm.solve_ik(g, false).reachable;
-        dg = norm3(g);
+        goal_ok = arm.solve_ik_preferred(g, false).reachable;
+        dg = arm.distance_from_base(g);
     }
 
+    // Nearest reachable points for inputs outside the workspace
+    let snap_start = parsed_start
+        .filter(|_| !start_ok)
+        .and_then(|s| overlay.start_snap.get(arm, &overlay.start_text, s));
+    let snap_goal = parsed_goal
+        .filter(|_| !goal_ok)
+        .and_then(|g| overlay.goal_snap.get(arm, &overlay.goal_text, g));
+    let reach_text = |ok: bool, dist: f64, snap: Option<WorkspaceProjection>| match (ok, snap) {
+        (true, _) => format!("YES   |p|={:.3}", dist),
+        (false, Some(p)) => format!("NO    {:.3} m outside", p.error_m),
+        (false, None) => "NO".to_string(),
+    };
+
     render::draw_text_small(
         d,
         font,
@@ -290,7 +367,7 @@ where
     render::draw_text_small(
         d,
         font,
-        format!("START reachable: {}   |p|={:.3}", if start_ok { "YES" } else { "NO" }, ds).as_str(),
+        format!("START reachable: {}", reach_text(start_ok, ds, snap_start)).as_str(),
         x0 + pad,
         y,
         18.0,
@@ -301,7 +378,7 @@ where
     render::draw_text_small(
         d,
         font,
-        format!("GOAL  reachable: {}   |p|={:.3}", if goal_ok { "YES" } else { "NO" }, dg).as_str(),
+        format!("GOAL  reachable: {}", reach_text(goal_ok, dg, snap_goal)).as_str(),
         x0 + pad,
         y,
         18.0,
@@ -309,11 +386,13 @@ where
     );
     y += 26;
 
-    // Play/Pause button
+    // Play/Pause button, narrowed to make room for SNAP when an input is out of reach
+    let can_snap = paused && (snap_start.is_some() || snap_goal.is_some());
+    let row_w = (w - 2 * pad) as f32;
     let btn = Rectangle {
         x: (x0 + pad) as f32,
         y: y as f32,
-        width: (w - 2 * pad) as f32,
+        width: if can_snap { 120.0 } else { row_w },
         height: 36.0,
     };
 
@@ -327,6 +406,33 @@ where
     d.draw_rectangle_rounded_lines(btn, 0.18, 8, Color::new(230, 230, 230, 255));
     render::draw_text_bold(d, font, label, btn.x as i32 + 10, btn.y as i32 + 7, 22.0, Color::RAYWHITE);
 
+    if can_snap {
+        let snap_btn = Rectangle {
+            x: btn.x + btn.width + 8.0,
+            y: btn.y,
+            width: row_w - btn.width - 8.0,
+            height: 36.0,
+        };
+        d.draw_rectangle_rounded(snap_btn, 0.18, 8, Color::new(50, 90, 140, 220));
+        d.draw_rectangle_rounded_lines(snap_btn, 0.18, 8, Color::new(230, 230, 230, 255));
+        render::draw_text_bold(d, font, "SNAP TO WORKSPACE", snap_btn.x as i32 + 10, snap_btn.y as i32 + 9, 18.0, Color::RAYWHITE);
+
+        if input.mouse_click && point_in_rect(input.mouse_pos, snap_btn) {
+            let mut notes = Vec::new();
+            if let Some(p) = snap_start {
+                overlay.start_text = format_vec3(p.point);
+                notes.push(format!("START moved {:.3} m", p.error_m));
+            }
+            if let Some(p) = snap_goal {
+                overlay.goal_text = format_vec3(p.point);
+                notes.push(format!("GOAL moved {:.3} m", p.error_m));
+            }
+            overlay.local_error = None;
+            overlay.local_note = Some(notes.join(", "));
+            return OverlayAction::None;
+        }
+    }
+
     let pressed_btn = input.mouse_click && point_in_rect(input.mouse_pos, btn);
     let pressed_enter = input.key_enter;
 
@@ -345,12 +451,16 @@ where
             return OverlayAction::Paused(true);
         };
 
-        let ik_home = arm.solve_ik(home, false);
-        let ik_s = arm.solve_ik(s, false);
-        let ik_g = arm.solve_ik(g, false);
+        let ik_home = arm.solve_ik_preferred(home, false);
+        let ik_s = arm.solve_ik_preferred(s, false);
+        let ik_g = arm.solve_ik_preferred(g, false);
 
         if !(ik_home.reachable && ik_s.reachable && ik_g.reachable) {
-            overlay.local_error = Some("OUT OF REACH! Choose points inside workspace (z>=0).".to_string());
+            let violation = [&ik_home, &ik_s, &ik_g].iter().find_map(|ik| ik.violation);
+            overlay.local_error = Some(match violation {
+                Some(v) => format!("JOINT LIMIT! {} out of range.", JOINT_NAMES[v.joint]),
+                None => "OUT OF REACH! Choose points inside workspace (z>=0).".to_string(),
+            });
             return OverlayAction::Paused(true);
         }
 
@@ -366,7 +476,174 @@ where
     }
     if let Some(e) = overlay.local_error.as_deref() {
         render::draw_text_bold(d, font, e, x0 + pad, y, 18.0, Color::RED);
+        y += 22;
+    }
+    if let Some(n) = overlay.local_note.as_deref() {
+        render::draw_text_small(d, font, n, x0 + pad, y, 18.0, Color::new(140, 200, 255, 255));
     }
 
     OverlayAction::None
 }
+
+pub fn draw_runtime_panel<D, F>(d: &mut D, font: F, status: &RuntimeStatus, screen_w: i32)
+where
+    D: RaylibDraw,
+    F: AsRef<raylib::ffi::Font> + Copy,
+{
+    let pad = 12;
+    let w = 300;
+    let h = if status.tracking_error.is_some() { 586 } else { 478 };
+    let x0 = screen_w - w - 14;
+    let y0 = 14;
+
+    d.draw_rectangle(x0, y0, w, h, Color::new(18, 18, 18, 230));
+    d.draw_rectangle_lines(x0, y0, w, h, Color::new(200, 200, 200, 255));
+
+    let mut y = y0 + pad;
+
+    render::draw_text_small(d, font, status.control_text, x0 + pad, y, 18.0, Color::SKYBLUE);
+    y += 22;
+    render::draw_text_small(d, font, status.strategy_text, x0 + pad, y, 18.0, Color::SKYBLUE);
+    y += 22;
+    render::draw_text_small(d, font, status.physics_text, x0 + pad, y, 18.0, Color::SKYBLUE);
+    y += 22;
+    render::draw_text_small(d, font, status.workspace_text, x0 + pad, y, 18.0, Color::SKYBLUE);
+    y += 22;
+    render::draw_text_small(d, font, status.export_note, x0 + pad, y, 18.0, Color::new(180, 180, 180, 255));
+    y += 26;
+
+    render::draw_text_small(
+        d,
+        font,
+        format!("{:<9}{:>8}{:>9}{:>9}", "deg", "model", "encoder", "per s").as_str(),
+        x0 + pad,
+        y,
+        18.0,
+        Color::new(180, 180, 180, 255),
+    );
+    y += 20;
+
+    let q = [status.q.q0_yaw, status.q.q1_pitch, status.q.q2_pitch];
+    let q_hw = [status.q_hardware.q0_yaw, status.q_hardware.q1_pitch, status.q_hardware.q2_pitch];
+    for i in 0..3 {
+        render::draw_text_small(
+            d,
+            font,
+            format!(
+                "{:<9}{:>8.1}{:>9.1}{:>9.1}",
+                JOINT_NAMES[i],
+                q[i].to_degrees(),
+                q_hw[i].to_degrees(),
+                status.qdot[i].to_degrees()
+            )
+            .as_str(),
+            x0 + pad,
+            y,
+            18.0,
+            Color::RAYWHITE,
+        );
+        y += 20;
+    }
+    render::draw_text_small(
+        d,
+        font,
+        format!("max step {:.2} deg/tick ({})", status.max_step.1.to_degrees(), JOINT_NAMES[status.max_step.0]).as_str(),
+        x0 + pad,
+        y,
+        18.0,
+        Color::new(180, 180, 180, 255),
+    );
+    y += 26;
+
+    render::draw_text_small(d, font, status.payload_text, x0 + pad, y, 18.0, Color::SKYBLUE);
+    y += 22;
+    render::draw_text_small(
+        d,
+        font,
+        format!(
+            "{:<9}{:>8}{:>18}",
+            "N m",
+            "now",
+            if status.torque_stats_complete { "last cycle rms/pk" } else { "cycle rms/pk" }
+        )
+        .as_str(),
+        x0 + pad,
+        y,
+        18.0,
+        Color::new(180, 180, 180, 255),
+    );
+    y += 20;
+    let rms = status.torque_stats.rms();
+    for i in 0..3 {
+        render::draw_text_small(
+            d,
+            font,
+            format!(
+                "{:<9}{:>8.1}{:>9.1}{:>9.1}",
+                JOINT_NAMES[i], status.torque[i], rms[i], status.torque_stats.peak[i]
+            )
+            .as_str(),
+            x0 + pad,
+            y,
+            18.0,
+            Color::RAYWHITE,
+        );
+        y += 20;
+    }
+    y += 6;
+
+    if let Some((now, peak)) = status.tracking_error {
+        render::draw_text_small(d, font, status.controller_text, x0 + pad, y, 18.0, Color::SKYBLUE);
+        y += 22;
+        render::draw_text_small(
+            d,
+            font,
+            format!("{:<9}{:>8}{:>9}", "err deg", "now", "peak").as_str(),
+            x0 + pad,
+            y,
+            18.0,
+            Color::new(180, 180, 180, 255),
+        );
+        y += 20;
+        for i in 0..3 {
+            render::draw_text_small(
+                d,
+                font,
+                format!("{:<9}{:>8.3}{:>9.3}", JOINT_NAMES[i], now[i].to_degrees(), peak[i].to_degrees()).as_str(),
+                x0 + pad,
+                y,
+                18.0,
+                Color::RAYWHITE,
+            );
+            y += 20;
+        }
+        y += 6;
+    }
+
+    if status.branch_switched {
+        render::draw_text_bold(d, font, "ELBOW BRANCH SWITCHED", x0 + pad, y, 18.0, Color::ORANGE);
+        y += 22;
+    }
+
+    if status.rate_limited {
+        render::draw_text_bold(d, font, "JOINT SPEED/ACCEL LIMITED", x0 + pad, y, 18.0, Color::ORANGE);
+        y += 22;
+    }
+
+    if status.path_over_limits {
+        render::draw_text_bold(d, font, "PATH EXCEEDS JOINT LIMITS", x0 + pad, y, 18.0, Color::ORANGE);
+        y += 22;
+    }
+
+    if let Some(kind) = status.singularity {
+        render::draw_text_bold(
+            d,
+            font,
+            format!("NEAR SINGULARITY: {}", kind.label()).as_str(),
+            x0 + pad,
+            y,
+            18.0,
+            Color::ORANGE,
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use manipulator3d::robot::{ActuatorParams, JointAngles, LinkParams, RobotArm};
+use manipulator3d::sim::{Integrator, PhysicsParams, PhysicsSim};
+
+/// The default arm with `yaw` driving the base; the yaw joint carries no gravity load.
+fn arm(yaw: ActuatorParams) -> RobotArm {
+    RobotArm::new(
+        LinkParams { length_m: 3.0, mass_kg: 2.0, ..Default::default() },
+        LinkParams { length_m: 2.6, mass_kg: 1.6, ..Default::default() },
+    )
+    .with_actuators([yaw, ActuatorParams::IDEAL, ActuatorParams::IDEAL])
+}
+
+/// Physics sim holding both pitch joints against gravity while `yaw_torque(t)` drives the base.
+fn run(arm: &RobotArm, seconds: f64, yaw_torque: impl Fn(f64) -> f64, mut each_step: impl FnMut(&PhysicsSim)) {
+    let params = PhysicsParams { integrator: Integrator::Rk4, ..Default::default() };
+    let mut sim = PhysicsSim::new(params, JointAngles::from([0.0, 0.4, -0.8]));
+    for _ in 0..(seconds * params.rate_hz).round() as usize {
+        let hold = arm.gravity_torques(sim.state().q);
+        sim.step(arm, [yaw_torque(sim.time_s()), hold[1], hold[2]]);
+        each_step(&sim);
+    }
+}
+
+#[test]
+fn drive_limits_and_friction_law() {
+    let drive = ActuatorParams {
+        gear_ratio: -50.0,
+        rotor_inertia: 2e-4,
+        viscous_nm_s_per_rad: 0.5,
+        coulomb_nm: 3.0,
+        static_nm: 2.0,
+        max_motor_torque_nm: 1.5,
+        ..ActuatorParams::IDEAL
+    };
+    // Gear ratio sign is the motor direction; limits and inertia use its magnitude
+    assert_eq!(drive.max_joint_torque(), 75.0);
+    assert!((drive.reflected_inertia() - 0.5).abs() < 1e-12);
+    assert_eq!(drive.saturate(-100.0), -75.0);
+    assert_eq!(drive.saturate(10.0), 10.0);
+
+    assert_eq!(drive.friction(2.0, 1.0), 4.0);
+    assert_eq!(drive.friction(-2.0, -1.0), -4.0);
+    // Breakaway is never below the sliding friction
+    assert_eq!(drive.breakaway_nm(), 3.0);
+    assert!(!drive.has_backlash());
+}
+
+#[test]
+fn static_friction_holds_until_breakaway_and_sticks_again() {
+    let arm = arm(ActuatorParams {
+        gear_ratio: 100.0,
+        rotor_inertia: 1e-5,
+        coulomb_nm: 3.0,
+        static_nm: 5.0,
+        ..ActuatorParams::IDEAL
+    });
+
+    // Below breakaway nothing moves
+    run(&arm, 0.5, |_| 4.5, |sim| assert_eq!(sim.state().q.q0_yaw, 0.0));
+
+    // Above it the joint slides; once the drive drops below kinetic friction it stops and stays
+    let mut stopped_at = None;
+    let mut last = 0.0;
+    run(
+        &arm,
+        2.0,
+        |t| if t < 0.5 { 6.0 } else { 2.0 },
+        |sim| {
+            let (q, qd) = (sim.state().q.q0_yaw, sim.state().qd[0]);
+            if sim.time_s() > 0.5 && qd == 0.0 {
+                stopped_at.get_or_insert(q);
+            }
+            if let Some(stop) = stopped_at {
+                assert_eq!(q, stop, "slipped again after sticking at t = {}", sim.time_s());
+            }
+            last = q;
+        },
+    );
+    assert!(stopped_at.is_some() && last > 0.0);
+}
+
+#[test]
+fn backlash_is_a_dead_band_between_motor_and_link() {
+    let backlash = 0.02;
+    let arm = arm(ActuatorParams {
+        gear_ratio: 100.0,
+        rotor_inertia: 1e-5,
+        backlash_rad: backlash,
+        ..ActuatorParams::IDEAL
+    });
+
+    // The motor starts mid-play: it crosses half the backlash before the link feels it
+    let mut engaged_at = None;
+    run(
+        &arm,
+        0.2,
+        |_| 0.5,
+        |sim| {
+            let play = sim.motor_state().q.q0_yaw - sim.state().q.q0_yaw;
+            assert!(play.abs() <= 0.5 * backlash + 1e-12);
+            if engaged_at.is_none() {
+                if play < 0.5 * backlash - 1e-12 {
+                    assert_eq!(sim.state().q.q0_yaw, 0.0, "link moved inside the play");
+                } else {
+                    engaged_at = Some(sim.time_s());
+                }
+            }
+        },
+    );
+    // 0.5 N m on a 0.1 kg m^2 reflected rotor: 5 rad/s^2, so half the play takes sqrt(2 * 0.01 / 5) s
+    let t = engaged_at.expect("motor never took up the play");
+    assert!((t - (2.0 * 0.5 * backlash / 5.0_f64).sqrt()).abs() < 2e-3, "engaged at {t}");
+}
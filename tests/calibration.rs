@@ -0,0 +1,79 @@
+use manipulator3d::robot::{
+    calibrate, read_measurements, CalibrationError, CalibrationParams, JointAngles, KinematicParams, LinkParams,
+    Measurement, RobotArm, Transform, Vec3,
+};
+
+fn arm() -> RobotArm {
+    RobotArm::new(
+        LinkParams { length_m: 3.0, ..Default::default() },
+        LinkParams { length_m: 2.6, ..Default::default() },
+    )
+    .with_tcp(Transform::from_translation(0.34, 0.0, 0.0))
+}
+
+/// Varied poses over the joint space.
+fn poses() -> Vec<JointAngles> {
+    let mut out = Vec::new();
+    for i in 0..4 {
+        for j in 0..3 {
+            for k in 0..3 {
+                out.push(JointAngles {
+                    q0_yaw: -2.0 + 1.3 * i as f64,
+                    q1_pitch: 0.2 + 0.5 * j as f64 + 0.1 * i as f64,
+                    q2_pitch: -1.5 + 1.1 * k as f64,
+                });
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn calibration_recovers_known_parameters() {
+    let arm = arm();
+    let truth = KinematicParams {
+        l1_m: 3.02,
+        l2_m: 2.585,
+        joint_offsets_rad: [0.01, -0.02, 0.015],
+        base_offset: Vec3::new(0.01, -0.005, 0.02),
+    };
+    let data: Vec<Measurement> = poses()
+        .into_iter()
+        .map(|q| Measurement { q, position: truth.predict(&arm, q) })
+        .collect();
+
+    let cal = calibrate(&arm, &data, &CalibrationParams::default()).unwrap();
+    assert!(cal.converged);
+    assert!(cal.initial_rms_m > 1e-2);
+    assert!(cal.rms_m < 1e-9, "rms {:.3e}", cal.rms_m);
+    for (fitted, expected) in cal.params.to_array().iter().zip(truth.to_array()) {
+        assert!((fitted - expected).abs() < 1e-7, "{:?} vs {truth:?}", cal.params);
+    }
+}
+
+#[test]
+fn csv_skips_header_and_comments() {
+    let text = "q0,q1,q2,x,y,z\n# laser tracker, 2024-03-01\n\n0.1, 0.2, -0.3, 1.0, 2.0, 3.0\n# repeat\n0.4,0.5,0.6,4,5,6\n";
+    let data = read_measurements(text.as_bytes()).unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(data[1].q.q2_pitch, 0.6);
+    assert_eq!(data[0].position, Vec3::new(1.0, 2.0, 3.0));
+}
+
+#[test]
+fn csv_rejects_malformed_rows() {
+    let bad_number = "q0,q1,q2,x,y,z\n0.1,0.2,0.3,1,2,3\n0.1,abc,0.3,1,2,3\n";
+    assert!(matches!(read_measurements(bad_number.as_bytes()), Err(CalibrationError::Parse { line: 3, .. })));
+
+    let short_row = "0.1,0.2,0.3,1,2\n";
+    assert!(matches!(read_measurements(short_row.as_bytes()), Err(CalibrationError::Parse { line: 1, .. })));
+}
+
+#[test]
+fn single_pose_is_unidentifiable() {
+    let arm = arm();
+    let q = JointAngles { q0_yaw: 0.3, q1_pitch: 0.8, q2_pitch: -1.1 };
+    let data = vec![Measurement { q, position: arm.forward_kinematics(q).tcp + Vec3::new(0.01, 0.0, 0.0) }; 10];
+    let err = calibrate(&arm, &data, &CalibrationParams::default()).unwrap_err();
+    assert!(matches!(err, CalibrationError::Unidentifiable(_)), "{err}");
+}
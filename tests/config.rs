@@ -0,0 +1,72 @@
+#![cfg(feature = "config")]
+
+use std::path::PathBuf;
+
+use manipulator3d::config::{ConfigError, SimConfig};
+
+/// Write `text` to a scratch file named after the test and load it.
+fn load(name: &str, text: &str) -> Result<SimConfig, ConfigError> {
+    let path: PathBuf = std::env::temp_dir().join(format!("manipulator3d-{name}-{}.toml", std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    let config = SimConfig::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    config
+}
+
+#[test]
+fn shipped_file_matches_defaults() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/default.toml");
+    assert_eq!(SimConfig::load(path).unwrap(), SimConfig::default());
+}
+
+#[test]
+fn partial_tables_keep_the_other_defaults() {
+    let config = load(
+        "partial",
+        "[arm.link1]\nmass_kg = 2.5\n\n[arm.link1.joint]\nmin_deg = -10\n\n[arm.yaw]\nmax_vel_rad_s = 1.5\n",
+    )
+    .unwrap();
+
+    let mut expected = SimConfig::default();
+    expected.arm.link1.mass_kg = 2.5;
+    expected.arm.link1.joint.min_deg = Some(-10.0);
+    expected.arm.yaw.max_vel_rad_s = Some(1.5);
+    assert_eq!(config, expected);
+}
+
+#[test]
+fn cable_wrap_replaces_default_yaw_limits() {
+    let config = load("wrap", "[arm]\ncable_wrap_turns = 2.0\n").unwrap();
+    assert_eq!(config.arm.cable_wrap_turns, Some(2.0));
+    assert_eq!((config.arm.yaw.min_deg, config.arm.yaw.max_deg), (None, None));
+    assert_eq!(config.arm.yaw.max_vel_rad_s, SimConfig::default().arm.yaw.max_vel_rad_s);
+
+    let [yaw, _, _] = config.arm.build().unwrap().joint_limits();
+    assert!((yaw.max_rad - 2.0 * std::f64::consts::TAU).abs() < 1e-12);
+    assert!((yaw.min_rad + 2.0 * std::f64::consts::TAU).abs() < 1e-12);
+}
+
+#[test]
+fn cable_wrap_and_yaw_limits_conflict() {
+    let err = load("wrap-conflict", "[arm]\ncable_wrap_turns = 2.0\n\n[arm.yaw]\nmax_deg = 300.0\n").unwrap_err();
+    assert!(matches!(&err, ConfigError::Invalid(errors) if errors.iter().any(|e| e.contains("cable_wrap_turns"))), "{err}");
+}
+
+#[test]
+fn base_pose_moves_built_and_urdf_arms() {
+    let urdf = concat!(env!("CARGO_MANIFEST_DIR"), "/resources/urdf/manipulator3d.urdf");
+    for urdf_line in ["", &format!("urdf = {{ path = \"{urdf}\" }}\n")] {
+        let text = format!("[arm]\n{urdf_line}base = {{ xyz = [0.0, 0.0, 6.0], rpy = [3.141592653589793, 0.0, 0.0] }}\n");
+        let config = load("base", &text).unwrap();
+        let mut unmounted = config.arm.clone();
+        unmounted.base = Default::default();
+
+        let (arm, plain) = (config.arm.build().unwrap(), unmounted.build().unwrap());
+        let mount = config.arm.base.transform();
+        for q in [[0.0, 0.0, 0.0], [0.7, 1.2, -1.9], [-2.5, 0.3, 2.4]] {
+            let q = q.into();
+            let expected = mount.transform_point(plain.forward_kinematics(q).tcp);
+            assert!(arm.forward_kinematics(q).tcp.distance(expected) < 1e-9, "{urdf_line}");
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use manipulator3d::robot::{JointAngles, LinkParams, RobotArm};
+use manipulator3d::sim::{
+    ControllerKind, ControllerParams, JointController, JointReference, JointState, PhysicsParams, PhysicsSim,
+};
+
+/// The default arm: 3.0 m / 2 kg and 2.6 m / 1.6 kg uniform rods.
+fn arm() -> RobotArm {
+    RobotArm::new(
+        LinkParams { length_m: 3.0, mass_kg: 2.0, ..Default::default() },
+        LinkParams { length_m: 2.6, mass_kg: 1.6, ..Default::default() },
+    )
+}
+
+/// Run a `kind` controller with default gains on the physics sim from `start` for
+/// `seconds`, tracking `reference(t)`. Returns the final position error and the peak error.
+fn run(
+    arm: &RobotArm,
+    kind: ControllerKind,
+    start: JointState,
+    seconds: f64,
+    reference: impl Fn(f64) -> JointReference,
+) -> ([f64; 3], [f64; 3]) {
+    let mut controller = JointController::new(ControllerParams { kind, ..Default::default() });
+    let mut sim = PhysicsSim::new(PhysicsParams::default(), start.q);
+    sim.reset(start);
+    let dt = sim.step_size();
+    for _ in 0..(seconds / dt).round() as usize {
+        let r = reference(sim.time_s());
+        let tau = controller.torque(arm, &r, &sim.state(), dt);
+        sim.step(arm, tau);
+    }
+    let r = reference(sim.time_s()).q.to_array();
+    let q = sim.state().q.to_array();
+    (std::array::from_fn(|i| r[i] - q[i]), controller.peak_error())
+}
+
+#[test]
+fn controllers_settle_on_a_step() {
+    let arm = arm();
+    let start = JointState { q: JointAngles::from([0.0, 0.6, -0.9]), qd: [0.0; 3] };
+    let step = JointReference {
+        q: JointAngles::from([0.2, 0.8, -0.7]),
+        ..Default::default()
+    };
+    for kind in [ControllerKind::Pid, ControllerKind::ComputedTorque] {
+        let (error, _) = run(&arm, kind, start, 8.0, |_| step);
+        assert!(error.iter().all(|e| e.abs() < 1e-3), "{kind:?}: final error {error:?}");
+    }
+}
+
+#[test]
+fn computed_torque_with_the_exact_model_tracks_without_error() {
+    let arm = arm();
+    // Smooth swing on every joint, starting on the reference
+    let (q0, amp, w) = ([0.0, 0.6, -0.9], [0.5, 0.3, 0.6], 2.0);
+    let reference = |t: f64| {
+        let (s, c) = (w * t).sin_cos();
+        JointReference {
+            q: JointAngles::from(std::array::from_fn(|i| q0[i] + amp[i] * s)),
+            qd: std::array::from_fn(|i| amp[i] * w * c),
+            qdd: std::array::from_fn(|i| -amp[i] * w * w * s),
+        }
+    };
+    let start = JointState { q: reference(0.0).q, qd: reference(0.0).qd };
+
+    // What is left comes from holding the torque over each 1 ms physics step
+    let (_, peak) = run(&arm, ControllerKind::ComputedTorque, start, 3.0, reference);
+    assert!(peak.iter().all(|e| *e < 1e-4), "peak tracking error {peak:?}");
+}
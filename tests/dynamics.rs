@@ -0,0 +1,90 @@
+use manipulator3d::robot::{JointAngles, LinkParams, Payload, RobotArm, Transform, GRAVITY};
+
+/// The default arm: 3.0 m / 2 kg and 2.6 m / 1.6 kg uniform rods.
+fn arm() -> RobotArm {
+    RobotArm::new(
+        LinkParams { length_m: 3.0, mass_kg: 2.0, ..Default::default() },
+        LinkParams { length_m: 2.6, mass_kg: 1.6, ..Default::default() },
+    )
+}
+
+fn states() -> [(JointAngles, [f64; 3], [f64; 3]); 3] {
+    [
+        ([0.0, 0.0, 0.0].into(), [0.0; 3], [1.0, -2.0, 0.5]),
+        ([0.7, 1.2, -1.9].into(), [0.4, -0.8, 1.1], [-0.3, 0.9, 2.0]),
+        ([-2.5, 0.3, 2.4].into(), [-1.5, 0.2, -0.6], [0.0, 0.0, 0.0]),
+    ]
+}
+
+#[test]
+fn horizontal_arm_gravity_matches_closed_form() {
+    let arm = arm();
+    let g = -GRAVITY.z;
+    let (l1, l2, m1, m2) = (3.0, 2.6, 2.0, 1.6);
+
+    // Stretched out horizontally, with the yaw anywhere
+    for yaw in [0.0, 1.1, -2.7] {
+        let tau = arm.inverse_dynamics([yaw, 0.0, 0.0].into(), [0.0; 3], [0.0; 3]);
+        let expected = [0.0, g * (m1 * l1 / 2.0 + m2 * (l1 + l2 / 2.0)), g * m2 * l2 / 2.0];
+        for (t, e) in tau.iter().zip(expected) {
+            assert!((t - e).abs() < 1e-9, "yaw {yaw}: {tau:?} vs {expected:?}");
+        }
+        assert_eq!(arm.gravity_torques([yaw, 0.0, 0.0].into()), tau);
+    }
+}
+
+#[test]
+fn forward_dynamics_inverts_inverse_dynamics() {
+    let arm = arm();
+    for (q, qd, qdd) in states() {
+        let tau = arm.inverse_dynamics(q, qd, qdd);
+        let back = arm.forward_dynamics(q, qd, tau);
+        for (a, b) in back.iter().zip(qdd) {
+            assert!((a - b).abs() < 1e-9, "{q:?}: {back:?} vs {qdd:?}");
+        }
+    }
+}
+
+#[test]
+fn mass_matrix_is_symmetric_positive_definite() {
+    let arm = arm();
+    for (q, _, _) in states() {
+        let m = arm.mass_matrix(q);
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!((m[i][j] - m[j][i]).abs() < 1e-9, "{q:?}: {m:?}");
+            }
+        }
+        // Leading principal minors
+        let minor2 = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+        assert!(m[0][0] > 0.0 && minor2 > 0.0 && m.determinant() > 0.0, "{q:?}: {m:?}");
+    }
+}
+
+#[test]
+fn payload_adds_its_weight_at_the_tool_com() {
+    let tcp = 0.34;
+    let mut arm = arm().with_tcp(Transform::from_translation(tcp, 0.0, 0.0));
+    let g = -GRAVITY.z;
+    let q = JointAngles::default();
+    let empty = arm.gravity_torques(q);
+
+    // A sphere held at its surface hangs one radius further out along the tool axis
+    let (mass, radius) = (1.5, 0.1);
+    arm.set_payload(Some(Payload::solid_sphere(mass, radius)));
+    let loaded = arm.gravity_torques(q);
+    let arm_to_com = [3.0 + 2.6 + tcp + radius, 2.6 + tcp + radius];
+    for (i, lever) in arm_to_com.into_iter().enumerate() {
+        let extra = loaded[i + 1] - empty[i + 1];
+        assert!((extra - g * mass * lever).abs() < 1e-9, "joint {}: {extra} vs {}", i + 1, g * mass * lever);
+    }
+
+    // Forearm straight up: the offset along the tool axis stacks the COM over the elbow
+    let up = JointAngles::from([0.0, 0.0, std::f64::consts::FRAC_PI_2]);
+    arm.set_payload(None);
+    let empty = arm.gravity_torques(up);
+    arm.set_payload(Some(Payload::solid_sphere(mass, radius)));
+    let extra = arm.gravity_torques(up)[1] - empty[1];
+    assert!((extra - g * mass * 3.0).abs() < 1e-9, "{extra}");
+    assert!((arm.gravity_torques(up)[2] - empty[2]).abs() < 1e-9);
+}
@@ -0,0 +1,76 @@
+use manipulator3d::robot::{ActuatorParams, JointAngles, LinkParams, RobotArm};
+use manipulator3d::sim::{Integrator, JointState, PhysicsParams, PhysicsSim};
+
+/// The default arm: 3.0 m / 2 kg and 2.6 m / 1.6 kg uniform rods, no joint limits.
+fn arm() -> RobotArm {
+    RobotArm::new(
+        LinkParams { length_m: 3.0, mass_kg: 2.0, ..Default::default() },
+        LinkParams { length_m: 2.6, mass_kg: 1.6, ..Default::default() },
+    )
+}
+
+fn sim(integrator: Integrator, rate_hz: f64, q: JointAngles) -> PhysicsSim {
+    PhysicsSim::new(PhysicsParams { rate_hz, integrator, ..Default::default() }, q)
+}
+
+/// Largest joint difference between two states.
+fn distance(a: &JointState, b: &JointState) -> f64 {
+    let (qa, qb) = (a.q.to_array(), b.q.to_array());
+    (0..3).map(|i| (qa[i] - qb[i]).abs()).fold(0.0, f64::max)
+}
+
+#[test]
+fn state_holds_without_net_torque() {
+    let arm = arm();
+    let q = JointAngles::from([0.4, 0.7, -1.2]);
+    for integrator in [Integrator::SemiImplicitEuler, Integrator::Rk4] {
+        let mut sim = sim(integrator, 1000.0, q);
+        // Holding torque cancels gravity exactly
+        for _ in 0..1000 {
+            let tau = arm.gravity_torques(sim.state().q);
+            sim.step(&arm, tau);
+        }
+        let s = sim.state();
+        assert!(distance(&s, &JointState { q, qd: [0.0; 3] }) < 1e-9, "{integrator:?}: {s:?}");
+        assert!(s.qd.iter().all(|v| v.abs() < 1e-9), "{integrator:?}: {s:?}");
+        assert!((sim.time_s() - 1.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn rk4_is_more_accurate_than_euler() {
+    let arm = arm();
+    let q = JointAngles::from([0.0, 0.3, -0.5]);
+    let swing = |integrator, rate_hz: f64| {
+        let mut sim = sim(integrator, rate_hz, q);
+        for _ in 0..(0.5 * rate_hz) as usize {
+            sim.step(&arm, [0.0; 3]);
+        }
+        sim.state()
+    };
+
+    // Half a second of free swing under gravity
+    let reference = swing(Integrator::Rk4, 20_000.0);
+    let euler = distance(&swing(Integrator::SemiImplicitEuler, 200.0), &reference);
+    let rk4 = distance(&swing(Integrator::Rk4, 200.0), &reference);
+    assert!(rk4 < 1e-6, "RK4 error {rk4:.3e}");
+    assert!(rk4 * 100.0 < euler, "RK4 {rk4:.3e} vs Euler {euler:.3e}");
+}
+
+#[test]
+fn torque_saturates_at_the_motor_limit() {
+    let drive = ActuatorParams {
+        gear_ratio: 10.0,
+        max_motor_torque_nm: 2.0,
+        ..ActuatorParams::IDEAL
+    };
+    let arm = arm().with_actuators([drive; 3]);
+    let mut sim = sim(Integrator::Rk4, 1000.0, JointAngles::default());
+
+    sim.step(&arm, [5.0, -15.0, 100.0]);
+    assert_eq!(sim.applied_torque(), [5.0, -15.0, 20.0]);
+    assert!(sim.saturated());
+
+    sim.step(&arm, [5.0, -15.0, 20.0]);
+    assert!(!sim.saturated());
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "urdf")]
+
+use manipulator3d::robot::{JointAngles, JointLimits, LinkParams, RobotArm, Transform, UrdfError, UrdfModel};
+
+#[test]
+fn joint_cycle_is_a_topology_error() {
+    // a is the only root; b and c are each other's parent
+    let text = r#"<robot name="loop">
+        <link name="a"/><link name="b"/><link name="c"/>
+        <joint name="bc" type="continuous"><parent link="b"/><child link="c"/><axis xyz="0 0 1"/></joint>
+        <joint name="cb" type="continuous"><parent link="c"/><child link="b"/><axis xyz="0 0 1"/></joint>
+    </robot>"#;
+    let err = UrdfModel::parse(text).unwrap_err();
+    assert!(matches!(&err, UrdfError::Topology(e) if e.contains("cycle")), "{err}");
+}
+
+#[test]
+fn chain_stops_on_a_hand_built_cycle() {
+    let text = r#"<robot name="line">
+        <link name="a"/><link name="b"/><link name="c"/>
+        <joint name="ab" type="continuous"><parent link="a"/><child link="b"/><axis xyz="0 0 1"/></joint>
+        <joint name="bc" type="continuous"><parent link="b"/><child link="c"/><axis xyz="0 0 1"/></joint>
+    </robot>"#;
+    let mut model = UrdfModel::parse(text).unwrap();
+    // Turn a -> b into c -> b, bypassing the checks in `parse`
+    model.joints[0].parent = "c".to_string();
+    assert!(matches!(model.chain(Some("b")), Err(UrdfError::Topology(_))));
+}
+
+fn limits(min_deg: f64, max_deg: f64, max_vel_rad_s: f64) -> JointLimits {
+    JointLimits {
+        min_rad: min_deg.to_radians(),
+        max_rad: max_deg.to_radians(),
+        max_vel_rad_s,
+        max_acc_rad_s2: 8.0,
+    }
+}
+
+#[test]
+fn exported_urdf_round_trips_forward_kinematics() {
+    // Tilted wall mount, offset from the origin
+    let base = Transform::from_translation(0.5, -0.2, 0.3) * Transform::rot_z(0.7) * Transform::rot_x(0.4);
+    let arm = RobotArm::new(
+        LinkParams { length_m: 3.0, mass_kg: 2.0, limits: limits(-20.0, 200.0, 2.0), ..Default::default() },
+        LinkParams { length_m: 2.6, mass_kg: 1.6, limits: limits(-165.0, 165.0, 2.5), ..Default::default() },
+    )
+    .with_yaw_limits(limits(-540.0, 540.0, 2.0))
+    .with_base(base)
+    .with_tcp(Transform::from_translation(0.34, 0.0, 0.05));
+
+    let mut urdf = Vec::new();
+    arm.write_urdf("roundtrip", &mut urdf).unwrap();
+    let model = UrdfModel::parse(std::str::from_utf8(&urdf).unwrap()).unwrap();
+    let back = model.to_robot_arm(Some("tcp")).unwrap();
+
+    for q in [[0.0, 0.0, 0.0], [0.7, 1.2, -1.9], [-2.5, 0.3, 2.4], [3.0, 2.9, -0.6]] {
+        let q = JointAngles::from(q);
+        let (a, b) = (arm.forward_kinematics(q), back.forward_kinematics(q));
+        assert!(a.tcp.distance(b.tcp) < 1e-9, "q = {q:?}: {:?} vs {:?}", a.tcp, b.tcp);
+    }
+
+    for (a, b) in [(arm.link1(), back.link1()), (arm.link2(), back.link2())] {
+        assert!((a.mass_kg - b.mass_kg).abs() < 1e-12);
+        assert!((a.inertia_cm - b.inertia_cm).abs() < 1e-9 && (a.inertia_joint - b.inertia_joint).abs() < 1e-9);
+    }
+
+    // Position and rate limits survive; URDF has no acceleration limit, so that is dropped
+    for (a, b) in arm.joint_limits().iter().zip(back.joint_limits()) {
+        assert_eq!((a.min_rad, a.max_rad), (b.min_rad, b.max_rad));
+        assert_eq!(a.max_vel_rad_s, b.max_vel_rad_s);
+        assert_eq!(b.max_acc_rad_s2, f64::INFINITY);
+    }
+}
+
+#[test]
+fn link_inertials_are_kept_as_given() {
+    // Upper arm: 2 kg with its COM at 1.0 m of a 3.0 m link, far from a uniform rod
+    let text = r#"<robot name="lumped">
+        <link name="base"/><link name="yaw"/><link name="tool"/>
+        <link name="upper"><inertial><origin xyz="1.0 0 0"/><mass value="2.0"/>
+            <inertia ixx="0.1" ixy="0" ixz="0" iyy="5.0" iyz="0" izz="5.0"/></inertial></link>
+        <link name="fore"><inertial><origin xyz="0.5 0 0"/><mass value="1.0"/>
+            <inertia ixx="0.1" ixy="0" ixz="0" iyy="0.25" iyz="0" izz="0.25"/></inertial></link>
+        <joint name="q0" type="continuous"><parent link="base"/><child link="yaw"/><axis xyz="0 0 1"/></joint>
+        <joint name="q1" type="continuous"><parent link="yaw"/><child link="upper"/><axis xyz="0 -1 0"/></joint>
+        <joint name="q2" type="continuous"><parent link="upper"/><child link="fore"/><origin xyz="3.0 0 0"/><axis xyz="0 -1 0"/></joint>
+        <joint name="flange" type="fixed"><parent link="fore"/><child link="tool"/><origin xyz="2.0 0 0"/></joint>
+    </robot>"#;
+    let arm = UrdfModel::parse(text).unwrap().to_robot_arm(None).unwrap();
+
+    for (link, (mass, inertia_cm, inertia_joint, com)) in
+        [arm.link1(), arm.link2()].into_iter().zip([(2.0, 5.0, 7.0, 1.0), (1.0, 0.25, 0.5, 0.5)])
+    {
+        assert!((link.mass_kg - mass).abs() < 1e-12);
+        assert!((link.inertia_cm - inertia_cm).abs() < 1e-9, "{link:?}");
+        assert!((link.inertia_joint - inertia_joint).abs() < 1e-9, "{link:?}");
+        assert!((link.com_offset_m() - com).abs() < 1e-9, "{link:?}");
+    }
+}